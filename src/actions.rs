@@ -1,4 +1,5 @@
 use crate::{scan::Scan, types::NodeID};
+use std::path::PathBuf;
 use std::sync::{Arc, atomic::Ordering};
 
 pub async fn show_node(scan: Arc<Scan>, node_id: NodeID) {
@@ -8,18 +9,33 @@ pub async fn show_node(scan: Arc<Scan>, node_id: NodeID) {
     }
 }
 
-pub async fn trash_node(scan: Arc<Scan>, node_id: NodeID) {
-    if let Ok(mut tree) = scan.tree_mutex.lock() {
-        let node = tree.get_elem(node_id);
-        let trash_res = trash::delete(&node.path);
-        match trash_res {
+// an item that can't be trashed doesn't stop the others, (path, error) is returned for each
+// the tree is only locked to look the paths up and to take out what was trashed,
+// not while the files are moved
+pub async fn trash_nodes(scan: Arc<Scan>, node_ids: Vec<NodeID>) -> Vec<(String, String)> {
+    let paths: Vec<(NodeID, PathBuf)> = match scan.tree_mutex.lock() {
+        Ok(tree) => node_ids
+            .into_iter()
+            .map(|id| (id, tree.get_elem(id).path.clone()))
+            .collect(),
+        Err(_) => return vec![],
+    };
+    let mut failures = vec![];
+    let mut trashed = false;
+    for (node_id, path) in paths {
+        match trash::delete(&path) {
             Ok(_) => {
-                tree.invalidate_elem(node_id);
-                scan.update_signal.store(true, Ordering::SeqCst);
-            }
-            Err(e) => {
-                dbg!(e);
+                if let Ok(mut tree) = scan.tree_mutex.lock() {
+                    tree.invalidate_elem(node_id);
+                    trashed = true;
+                }
             }
+            Err(e) => failures.push((path.to_string_lossy().into_owned(), e.to_string())),
         }
     }
+    if trashed && let Ok(mut tree) = scan.tree_mutex.lock() {
+        tree.refill_largest_files();
+    }
+    scan.update_signal.store(true, Ordering::SeqCst);
+    failures
 }
@@ -0,0 +1,253 @@
+use crate::config;
+use crate::empty::find_empty;
+use crate::node_color::now_secs;
+use crate::old::{DAY, OldQuery, find_old_files};
+use crate::scan::{Scan, ScanOptions};
+use crate::settings::Settings;
+use crate::state::AppState;
+use crate::utils::bytes_display;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::Ordering;
+use std::thread;
+
+pub const USAGE: &str = "Usage:
+  spaceman [DIRECTORY]            open the window, scanning DIRECTORY if given
+  spaceman --last                 open the window, scanning the last directory again
+  spaceman --empty DIRECTORY      list empty directories and zero-byte files
+  spaceman [--older-than AGE] [--larger-than SIZE] DIRECTORY
+                                  list files neither modified nor accessed for AGE
+                                  (like 180d, 6m or 2y) and at least SIZE large
+                                  (like 500K, 100M or 2G)
+  add --json to a report to print it as JSON, --last can stand for DIRECTORY";
+
+// reports are printed to the terminal instead of opening the window
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Report {
+    Empty,
+    Old(OldQuery),
+}
+
+pub enum Command {
+    Gui(Option<PathBuf>),
+    Report(Report, PathBuf, bool),
+}
+
+// a number of days, or of weeks, months or years with w, m or y after it
+fn parse_age(value: &str) -> Result<u64, String> {
+    let (number, unit) = match value.char_indices().last() {
+        Some((i, c)) if c.is_ascii_alphabetic() => (&value[..i], c.to_ascii_lowercase()),
+        _ => (value, 'd'),
+    };
+    let days = match unit {
+        'd' => 1,
+        'w' => 7,
+        'm' => 30,
+        'y' => 365,
+        _ => return Err(format!("Unknown unit in age {}", value)),
+    };
+    number
+        .parse::<u64>()
+        .ok()
+        .and_then(|number| number.checked_mul(days * DAY))
+        .ok_or_else(|| format!("Invalid age {}", value))
+}
+
+// bytes, or K, M, G or T of them, counted in 1024s like the sizes shown
+fn parse_size(value: &str) -> Result<u64, String> {
+    let value = value.trim_end_matches(['B', 'b']);
+    let (number, unit) = match value.char_indices().last() {
+        Some((i, c)) if c.is_ascii_alphabetic() => (&value[..i], c.to_ascii_uppercase()),
+        _ => (value, ' '),
+    };
+    let power = match unit {
+        ' ' => 0,
+        'K' => 1,
+        'M' => 2,
+        'G' => 3,
+        'T' => 4,
+        _ => return Err(format!("Unknown unit in size {}", value)),
+    };
+    // "nan", "inf" and negative sizes parse as numbers too
+    match number.parse::<f64>() {
+        Ok(number) if number.is_finite() && number >= 0.0 => {
+            Ok((number * 1024_f64.powi(power)) as u64)
+        }
+        _ => Err(format!("Invalid size {}", value)),
+    }
+}
+
+pub fn parse(args: &[String]) -> Result<Command, String> {
+    let mut report = None;
+    let mut old_query: Option<OldQuery> = None;
+    let mut json = false;
+    let mut last = false;
+    let mut path = None;
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        let mut value = || args.next().ok_or_else(|| format!("{} needs a value", arg));
+        match arg.as_str() {
+            "--empty" => report = Some(Report::Empty),
+            "--older-than" => {
+                let min_age = parse_age(value()?)?;
+                old_query = Some(OldQuery {
+                    min_age,
+                    min_size: old_query.map_or(0, |q| q.min_size),
+                });
+            }
+            "--larger-than" => {
+                let min_size = parse_size(value()?)?;
+                old_query = Some(OldQuery {
+                    min_size,
+                    min_age: old_query.map_or(0, |q| q.min_age),
+                });
+            }
+            "--json" => json = true,
+            "--last" => last = true,
+            "-h" | "--help" => return Err(String::new()),
+            flag if flag.starts_with("--") => return Err(format!("Unknown option {}", flag)),
+            _ if path.is_some() => return Err(format!("Unexpected argument {}", arg)),
+            _ => path = Some(PathBuf::from(arg)),
+        }
+    }
+    let report = match (report, old_query) {
+        (Some(_), Some(_)) => return Err(String::from("Only one report can be made at a time")),
+        (report, old_query) => report.or(old_query.map(Report::Old)),
+    };
+    if last {
+        if path.is_some() {
+            return Err(String::from("--last can't be given with a directory"));
+        }
+        path = Some(
+            AppState::load()
+                .existing_roots()
+                .next()
+                .cloned()
+                .ok_or_else(|| String::from("No earlier scan to repeat"))?,
+        );
+    }
+    match (report, path) {
+        (None, _) if json => Err(String::from("--json needs a report")),
+        (None, path) => Ok(Command::Gui(path)),
+        (Some(report), Some(path)) => Ok(Command::Report(report, path, json)),
+        (Some(_), None) => Err(String::from("A report needs a directory to scan")),
+    }
+}
+
+fn json_string(s: &str) -> String {
+    let mut out = String::from("\"");
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c if (c as u32) < 0x20 => out.push_str(&format!("\\u{:04x}", c as u32)),
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+    out
+}
+
+fn json_list(items: impl Iterator<Item = String>) -> String {
+    format!("[{}]", items.collect::<Vec<_>>().join(","))
+}
+
+// scans the directory to the end, then prints the report
+pub fn run(report: Report, path: &Path, json: bool) -> Result<(), String> {
+    if !path.is_dir() {
+        return Err(format!("{} is not a directory", path.display()));
+    }
+    let (settings, _) = Settings::load();
+    config::set_tuning(settings.tuning);
+    let options = ScanOptions {
+        git: false,
+        ..settings.scan_options()
+    };
+    let scan = Scan::new(&path.to_string_lossy(), options);
+    while !scan.complete.load(Ordering::SeqCst) {
+        thread::sleep(config::tuning().update_period());
+    }
+    let tree = scan.tree_mutex.lock().map_err(|e| e.to_string())?;
+    match report {
+        Report::Empty => {
+            let empty = find_empty(&tree, 0);
+            if json {
+                let paths =
+                    |items: &[(_, String)]| json_list(items.iter().map(|(_, p)| json_string(p)));
+                println!(
+                    "{{\"empty_dirs\":{},\"zero_byte_files\":{}}}",
+                    paths(&empty.dirs),
+                    paths(&empty.files)
+                );
+            } else {
+                println!("Empty directories ({}):", empty.dirs.len());
+                for (_, path) in empty.dirs.iter() {
+                    println!("{}", path);
+                }
+                println!("Zero-byte files ({}):", empty.files.len());
+                for (_, path) in empty.files.iter() {
+                    println!("{}", path);
+                }
+            }
+        }
+        Report::Old(query) => {
+            let files = find_old_files(&tree, 0, query, now_secs());
+            let total: u64 = files.iter().map(|f| f.size).sum();
+            if json {
+                let files = json_list(files.iter().map(|f| {
+                    format!(
+                        "{{\"path\":{},\"size\":{},\"last_used\":{}}}",
+                        json_string(&f.path),
+                        f.size,
+                        f.last_used
+                    )
+                }));
+                println!("{{\"total_size\":{},\"files\":{}}}", total, files);
+            } else {
+                for file in files.iter() {
+                    println!("{}\t{}", bytes_display(file.size), file.path);
+                }
+                println!("{} in {} files", bytes_display(total), files.len());
+            }
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ages() {
+        assert_eq!(parse_age("10"), Ok(10 * DAY));
+        assert_eq!(parse_age("10d"), Ok(10 * DAY));
+        assert_eq!(parse_age("2w"), Ok(14 * DAY));
+        assert_eq!(parse_age("6M"), Ok(180 * DAY));
+        assert_eq!(parse_age("1y"), Ok(365 * DAY));
+        assert!(parse_age("3x").is_err());
+        assert!(parse_age("y").is_err());
+        assert!(parse_age("-1d").is_err());
+        assert!(parse_age("1.5y").is_err());
+        assert!(parse_age("99999999999999y").is_err());
+    }
+
+    #[test]
+    fn sizes() {
+        assert_eq!(parse_size("500"), Ok(500));
+        assert_eq!(parse_size("500B"), Ok(500));
+        assert_eq!(parse_size("1k"), Ok(1024));
+        assert_eq!(parse_size("1.5M"), Ok(1536 * 1024));
+        assert_eq!(parse_size("2GB"), Ok(2 * 1024 * 1024 * 1024));
+        assert_eq!(parse_size("1t"), Ok(1024_u64.pow(4)));
+        assert!(parse_size("1X").is_err());
+        assert!(parse_size("G").is_err());
+        assert!(parse_size("-1G").is_err());
+        assert!(parse_size("nan").is_err());
+        assert!(parse_size("inf").is_err());
+        assert!(parse_size("infM").is_err());
+        assert!(parse_size("1e999").is_err());
+    }
+}
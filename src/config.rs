@@ -1,12 +1,135 @@
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use std::sync::RwLock;
 use std::time::Duration;
 
 //pub const APP_NAME: &str = "com.github.salihgerdan.spaceman";
 pub const APP_TITLE: &str = "SpaceMan";
 // used to batch add entries into the file tree and update gui
 pub const UPDATE_PERIOD: Duration = Duration::from_millis(60);
+// duplicate files are first told apart by a hash of this many bytes from their start
+pub const PARTIAL_HASH_SIZE: usize = 16 * 1024;
+// the panels and summaries that go through the whole shown tree are redone at most this often
+// while scanning, they can take longer than a treemap update
+pub const REPORTS_PERIOD: Duration = Duration::from_millis(1000);
+// how many files the largest files panel lists
+pub const LARGEST_FILES: usize = 100;
+// how many scanned directories are remembered for the start screen
+pub const MAX_RECENT_ROOTS: usize = 10;
 
 // only affects the treemap view
 pub const MAX_VISIBLE_FS_DEPTH: usize = 16;
 pub const MIN_BOX_SIZE: f32 = 20.0;
 pub const BORDER: f32 = 1.0;
 pub const TEXT_SIZE: f32 = 16.0;
+// zooming into a directory and back out
+pub const ZOOM_DURATION: Duration = Duration::from_millis(200);
+// how far the mouse wheel can zoom in
+pub const MAX_ZOOM: f32 = 512.0;
+// children too small for a box of MIN_BOX_SIZE squared are shown together as one block,
+// its tooltip lists this many of the largest of them
+pub const SMALL_ITEMS_BREAKDOWN: usize = 5;
+
+// only affect the icicle view
+pub const MIN_ROW_HEIGHT: f32 = 22.0;
+pub const MIN_ICICLE_WIDTH: f32 = 2.0;
+
+// only affects the sunburst view
+pub const SUNBURST_RINGS: usize = 6;
+// in radians, smaller segments are left out
+pub const MIN_ARC_ANGLE: f32 = 0.01;
+
+// the values above that can be changed in the settings file, under [tuning]
+// the consts are their defaults
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Tuning {
+    // in milliseconds
+    pub update_period: u64,
+    pub max_visible_fs_depth: usize,
+    pub min_box_size: f32,
+    pub border: f32,
+    pub text_size: f32,
+}
+
+impl Default for Tuning {
+    fn default() -> Self {
+        Self {
+            update_period: UPDATE_PERIOD.as_millis() as u64,
+            max_visible_fs_depth: MAX_VISIBLE_FS_DEPTH,
+            min_box_size: MIN_BOX_SIZE,
+            border: BORDER,
+            text_size: TEXT_SIZE,
+        }
+    }
+}
+
+impl Tuning {
+    pub fn update_period(&self) -> Duration {
+        Duration::from_millis(self.update_period)
+    }
+
+    // values out of range are put back to their defaults, with a message for each
+    pub fn validate(&mut self) -> Vec<String> {
+        let default = Tuning::default();
+        let mut errors = vec![];
+        let mut check = |name: &str, ok: bool, range: &str, value: String| {
+            if !ok {
+                errors.push(format!("{} must be {}, not {}", name, range, value));
+            }
+            ok
+        };
+        if !check(
+            "update_period",
+            (10..=2000).contains(&self.update_period),
+            "between 10 and 2000 milliseconds",
+            self.update_period.to_string(),
+        ) {
+            self.update_period = default.update_period;
+        }
+        if !check(
+            "max_visible_fs_depth",
+            (1..=64).contains(&self.max_visible_fs_depth),
+            "between 1 and 64",
+            self.max_visible_fs_depth.to_string(),
+        ) {
+            self.max_visible_fs_depth = default.max_visible_fs_depth;
+        }
+        if !check(
+            "min_box_size",
+            (4.0..=200.0).contains(&self.min_box_size),
+            "between 4 and 200 pixels",
+            self.min_box_size.to_string(),
+        ) {
+            self.min_box_size = default.min_box_size;
+        }
+        if !check(
+            "border",
+            (0.0..=10.0).contains(&self.border),
+            "between 0 and 10 pixels",
+            self.border.to_string(),
+        ) {
+            self.border = default.border;
+        }
+        if !check(
+            "text_size",
+            (6.0..=48.0).contains(&self.text_size),
+            "between 6 and 48 pixels",
+            self.text_size.to_string(),
+        ) {
+            self.text_size = default.text_size;
+        }
+        errors
+    }
+}
+
+// the tuning in use, read by the scan thread and the layouts
+static TUNING: Lazy<RwLock<Tuning>> = Lazy::new(|| RwLock::new(Tuning::default()));
+
+pub fn tuning() -> Tuning {
+    *TUNING.read().unwrap()
+}
+
+pub fn set_tuning(tuning: Tuning) {
+    *TUNING.write().unwrap() = tuning;
+}
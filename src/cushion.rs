@@ -0,0 +1,100 @@
+use crate::config;
+use crate::types::{GUINode, RGBA, Rectangle};
+
+// Cushion treemaps, van Wijk & van de Wetering
+// https://www.win.tue.nl/~vanwijk/ctm.pdf
+// every nesting level adds a parabolic ridge over its rectangle, the sum of the
+// ridges is shaded as a surface lit from the top left
+
+// height of the outermost ridge, each level down gets RIDGE_FALLOFF times lower
+const RIDGE_HEIGHT: f32 = 0.5;
+const RIDGE_FALLOFF: f32 = 0.75;
+const AMBIENT: f32 = 0.3;
+const DIFFUSE: f32 = 0.7;
+const LIGHT: (f32, f32, f32) = (-1.0, -2.0, 10.0);
+
+// z = s1x * x + s2x * x^2 + s1y * y + s2y * y^2
+#[derive(Clone, Copy, Default)]
+struct Surface {
+    s1x: f32,
+    s2x: f32,
+    s1y: f32,
+    s2y: f32,
+}
+
+impl Surface {
+    fn add_ridge(mut self, rect: &Rectangle, height: f32) -> Self {
+        let (x1, x2) = (rect.x, rect.x + rect.width);
+        let (y1, y2) = (rect.y, rect.y + rect.height);
+        if x2 > x1 {
+            self.s1x += 4.0 * height * (x2 + x1) / (x2 - x1);
+            self.s2x -= 4.0 * height / (x2 - x1);
+        }
+        if y2 > y1 {
+            self.s1y += 4.0 * height * (y2 + y1) / (y2 - y1);
+            self.s2y -= 4.0 * height / (y2 - y1);
+        }
+        self
+    }
+
+    fn intensity(&self, x: f32, y: f32, light: (f32, f32, f32)) -> f32 {
+        let nx = -(2.0 * self.s2x * x + self.s1x);
+        let ny = -(2.0 * self.s2y * y + self.s1y);
+        let cos = (nx * light.0 + ny * light.1 + light.2) / (nx * nx + ny * ny + 1.0).sqrt();
+        AMBIENT + DIFFUSE * cos.max(0.0)
+    }
+}
+
+fn contains_center(outer: &Rectangle, inner: &Rectangle) -> bool {
+    outer.contains_point(inner.x + inner.width / 2.0, inner.y + inner.height / 2.0)
+}
+
+// renders the gui nodes into an RGBA image of the given size
+// gui nodes come in the order compute_gui_nodes yields them, each directory before its contents
+pub fn render(gui_nodes: &[GUINode], width: u32, height: u32, background: RGBA) -> Vec<u8> {
+    let to_byte = |v: f32| (v.clamp(0.0, 1.0) * 255.0) as u8;
+    let mut pixels: Vec<u8> = [
+        to_byte(background.r),
+        to_byte(background.g),
+        to_byte(background.b),
+        255,
+    ]
+    .repeat((width * height) as usize);
+
+    let len = (LIGHT.0 * LIGHT.0 + LIGHT.1 * LIGHT.1 + LIGHT.2 * LIGHT.2).sqrt();
+    let light = (LIGHT.0 / len, LIGHT.1 / len, LIGHT.2 / len);
+
+    // ancestors of the current node, with their accumulated surfaces
+    let border = config::tuning().border;
+    let mut stack: Vec<(Rectangle, Surface)> = vec![];
+    for gnode in gui_nodes {
+        while stack
+            .last()
+            .is_some_and(|(rect, _)| !contains_center(rect, &gnode.rect))
+        {
+            stack.pop();
+        }
+        let ridge = RIDGE_HEIGHT * RIDGE_FALLOFF.powi(stack.len() as i32);
+        let surface = stack
+            .last()
+            .map(|(_, s)| *s)
+            .unwrap_or_default()
+            .add_ridge(&gnode.rect, ridge);
+        stack.push((gnode.rect, surface));
+
+        let x_start = (gnode.rect.x + border).max(0.0) as u32;
+        let x_end = ((gnode.rect.x + gnode.rect.width - border).max(0.0) as u32).min(width);
+        let y_start = (gnode.rect.y + border).max(0.0) as u32;
+        let y_end = ((gnode.rect.y + gnode.rect.height - border).max(0.0) as u32).min(height);
+        for y in y_start..y_end {
+            for x in x_start..x_end {
+                let i = surface.intensity(x as f32 + 0.5, y as f32 + 0.5, light);
+                let offset = ((y * width + x) * 4) as usize;
+                pixels[offset] = to_byte(gnode.color.r * i);
+                pixels[offset + 1] = to_byte(gnode.color.g * i);
+                pixels[offset + 2] = to_byte(gnode.color.b * i);
+            }
+        }
+    }
+    pixels
+}
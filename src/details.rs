@@ -0,0 +1,66 @@
+use crate::owners::{group_name, user_name};
+use crate::types::{GitBytes, GitStatus, NodeID, Tree};
+
+// everything the details panel shows about a node
+#[derive(Debug, Clone)]
+pub struct NodeDetails {
+    pub name: String,
+    pub path: String,
+    pub is_file: bool,
+    pub size: u64,
+    pub disk_size: u64,
+    // shares of the parent and of the scanned directory, 0.0 to 1.0
+    pub parent_fraction: Option<f32>,
+    pub root_fraction: f32,
+    // everything below the node
+    pub files: u64,
+    pub dirs: u64,
+    pub largest_child: Option<(String, u64)>,
+    pub mtime: Option<u64>,
+    pub owner: String,
+    pub mode: Option<u32>,
+    pub is_mount_point: bool,
+    pub is_symlink: bool,
+    // of the file, and of the bytes below a directory
+    pub git: Option<GitStatus>,
+    pub git_bytes: GitBytes,
+}
+
+fn fraction(part: u64, whole: u64) -> f32 {
+    if whole > 0 {
+        part as f32 / whole as f32
+    } else {
+        0.0
+    }
+}
+
+pub fn node_details(tree: &Tree, id: NodeID) -> NodeDetails {
+    let node = tree.get_elem(id);
+
+    NodeDetails {
+        name: node.name.clone(),
+        path: node.path.to_string_lossy().into_owned(),
+        is_file: node.is_file,
+        size: node.size,
+        disk_size: node.disk_size,
+        parent_fraction: node
+            .parent
+            .map(|p| fraction(node.size, tree.get_elem(p).size)),
+        root_fraction: fraction(node.size, tree.get_elem(0).size),
+        files: node.file_count,
+        dirs: node.dir_count,
+        largest_child: node
+            .children
+            .iter()
+            .map(|c| tree.get_elem(*c))
+            .max_by_key(|c| c.size)
+            .map(|c| (c.name.clone(), c.size)),
+        mtime: node.mtime,
+        owner: format!("{}:{}", user_name(node.uid), group_name(node.gid)),
+        mode: node.mode,
+        is_mount_point: tree.is_mount_point(id),
+        is_symlink: node.is_symlink,
+        git: node.git,
+        git_bytes: node.git_bytes,
+    }
+}
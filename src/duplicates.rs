@@ -0,0 +1,380 @@
+use crate::config::PARTIAL_HASH_SIZE;
+use crate::scan::Scan;
+use crate::types::{NodeID, Tree};
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{HashMap, HashSet};
+use std::fs::File;
+use std::hash::Hasher;
+use std::io::{self, Read};
+use std::path::{Path, PathBuf};
+use std::sync::{
+    Arc, Mutex,
+    atomic::{AtomicUsize, Ordering},
+};
+use std::thread;
+
+// files with the same contents
+#[derive(Debug, Clone)]
+pub struct DuplicateGroup {
+    // of each copy
+    pub size: u64,
+    // with their paths for the panel, which can't lock the tree
+    pub nodes: Vec<(NodeID, String)>,
+}
+
+impl DuplicateGroup {
+    // all copies but one could go
+    pub fn wasted(&self) -> u64 {
+        self.size * (self.nodes.len() as u64 - 1)
+    }
+}
+
+struct Candidate {
+    id: NodeID,
+    path: PathBuf,
+    size: u64,
+}
+
+// files sharing their size with another, empty files and symlinks left out
+// hard links are one file, only the first path found to it is kept
+// walked from the root so that trashed nodes are left out
+fn candidates(tree: &Tree) -> Vec<Vec<Candidate>> {
+    let mut by_size: HashMap<u64, Vec<Candidate>> = HashMap::new();
+    let mut inodes = HashSet::new();
+    let mut stack = vec![0];
+    while let Some(id) = stack.pop() {
+        let node = tree.get_elem(id);
+        stack.extend(node.children.iter());
+        if let (Some(dev), Some(ino)) = (node.dev, node.ino)
+            && node.is_file
+            && !inodes.insert((dev, ino))
+        {
+            continue;
+        }
+        if node.is_file && !node.is_symlink && node.size > 0 {
+            by_size.entry(node.size).or_default().push(Candidate {
+                id: node.id,
+                path: node.path.clone(),
+                size: node.size,
+            });
+        }
+    }
+    by_size.into_values().filter(|g| g.len() > 1).collect()
+}
+
+// a 64 bit SipHash of the first limit bytes, or of the whole file
+fn hash_file(path: &Path, limit: Option<usize>) -> io::Result<u64> {
+    let mut file = File::open(path)?;
+    let mut hasher = DefaultHasher::new();
+    let mut buffer = vec![0; 64 * 1024];
+    let mut left = limit.unwrap_or(usize::MAX);
+    while left > 0 {
+        let want = buffer.len().min(left);
+        let read = file.read(&mut buffer[..want])?;
+        if read == 0 {
+            break;
+        }
+        hasher.write(&buffer[..read]);
+        left -= read;
+    }
+    Ok(hasher.finish())
+}
+
+// hashes the files on all cores, in the order given
+// files that can't be read get None and drop out
+fn hash_all(files: &[&Candidate], limit: Option<usize>) -> Vec<Option<u64>> {
+    let hashes = Mutex::new(vec![None; files.len()]);
+    let next = AtomicUsize::new(0);
+    let workers = thread::available_parallelism().map_or(4, |n| n.get());
+    thread::scope(|s| {
+        for _ in 0..workers {
+            s.spawn(|| {
+                loop {
+                    let i = next.fetch_add(1, Ordering::Relaxed);
+                    let Some(file) = files.get(i) else {
+                        break;
+                    };
+                    let hash = hash_file(&file.path, limit).ok();
+                    hashes.lock().unwrap()[i] = hash;
+                }
+            });
+        }
+    });
+    hashes.into_inner().unwrap()
+}
+
+// fills the buffer unless the file ends first, returns how much was read
+fn read_full(file: &mut File, buffer: &mut [u8]) -> io::Result<usize> {
+    let mut filled = 0;
+    while filled < buffer.len() {
+        let read = file.read(&mut buffer[filled..])?;
+        if read == 0 {
+            break;
+        }
+        filled += read;
+    }
+    Ok(filled)
+}
+
+fn same_contents(a: &Path, b: &Path) -> io::Result<bool> {
+    let (mut a, mut b) = (File::open(a)?, File::open(b)?);
+    let (mut buffer_a, mut buffer_b) = (vec![0; 64 * 1024], vec![0; 64 * 1024]);
+    loop {
+        let read_a = read_full(&mut a, &mut buffer_a)?;
+        let read_b = read_full(&mut b, &mut buffer_b)?;
+        if buffer_a[..read_a] != buffer_b[..read_b] {
+            return Ok(false);
+        }
+        if read_a == 0 {
+            return Ok(true);
+        }
+    }
+}
+
+// equal hashes don't prove equal contents, so the files are compared byte by byte
+// with the first one, those that differ are compared among themselves again
+// files that can't be read drop out
+fn split_by_contents(group: Vec<Candidate>) -> Vec<Vec<Candidate>> {
+    let mut split = vec![];
+    let mut left = group;
+    while left.len() > 1 {
+        let first = left.remove(0);
+        let mut same = vec![];
+        let mut rest = vec![];
+        for file in left {
+            match same_contents(&first.path, &file.path) {
+                Ok(true) => same.push(file),
+                Ok(false) => rest.push(file),
+                Err(_) => {}
+            }
+        }
+        if !same.is_empty() {
+            same.insert(0, first);
+            split.push(same);
+        }
+        left = rest;
+    }
+    split
+}
+
+// compares the groups on all cores, like hash_all
+fn split_all_by_contents(groups: Vec<Vec<Candidate>>) -> Vec<Vec<Candidate>> {
+    let groups: Vec<Mutex<Option<Vec<Candidate>>>> =
+        groups.into_iter().map(|g| Mutex::new(Some(g))).collect();
+    let split = Mutex::new(vec![]);
+    let next = AtomicUsize::new(0);
+    let workers = thread::available_parallelism().map_or(4, |n| n.get());
+    thread::scope(|s| {
+        for _ in 0..workers {
+            s.spawn(|| {
+                loop {
+                    let i = next.fetch_add(1, Ordering::Relaxed);
+                    let Some(group) = groups.get(i) else {
+                        break;
+                    };
+                    let group = group.lock().unwrap().take().unwrap_or_default();
+                    let confirmed = split_by_contents(group);
+                    split.lock().unwrap().extend(confirmed);
+                }
+            });
+        }
+    });
+    split.into_inner().unwrap()
+}
+
+// splits every group further by the hash of the files, groups left with one file are dropped
+fn split_by_hash(groups: Vec<Vec<Candidate>>, limit: Option<usize>) -> Vec<Vec<Candidate>> {
+    let files: Vec<&Candidate> = groups.iter().flatten().collect();
+    let mut hashes = hash_all(&files, limit).into_iter();
+
+    let mut split = vec![];
+    for group in groups {
+        let mut by_hash: HashMap<u64, Vec<Candidate>> = HashMap::new();
+        for file in group {
+            if let Some(hash) = hashes.next().unwrap() {
+                by_hash.entry(hash).or_default().push(file);
+            }
+        }
+        split.extend(by_hash.into_values().filter(|g| g.len() > 1));
+    }
+    split
+}
+
+// by size first, then by a hash of the start of the files, and only then by a hash of
+// the whole files, so that most files are never read in full
+// the tree is only locked while the candidates are collected
+pub async fn find_duplicates(scan: Arc<Scan>) -> Vec<DuplicateGroup> {
+    let by_size = match scan.tree_mutex.lock() {
+        Ok(tree) => candidates(&tree),
+        Err(_) => return vec![],
+    };
+    group_duplicates(by_size)
+}
+
+// the files with the same hashes are compared in full before they're reported
+// largest waste first
+fn group_duplicates(by_size: Vec<Vec<Candidate>>) -> Vec<DuplicateGroup> {
+    let by_start = split_by_hash(by_size, Some(PARTIAL_HASH_SIZE));
+    // files no larger than the partial hash have been read in full already
+    let (small, large): (Vec<_>, Vec<_>) = by_start
+        .into_iter()
+        .partition(|g| g[0].size <= PARTIAL_HASH_SIZE as u64);
+    let by_hash = small
+        .into_iter()
+        .chain(split_by_hash(large, None))
+        .collect();
+
+    let mut groups: Vec<DuplicateGroup> = split_all_by_contents(by_hash)
+        .into_iter()
+        .map(|g| DuplicateGroup {
+            size: g[0].size,
+            nodes: g
+                .into_iter()
+                .map(|c| (c.id, c.path.to_string_lossy().into_owned()))
+                .collect(),
+        })
+        .collect();
+    groups.sort_by_key(|g| std::cmp::Reverse(g.wasted()));
+    groups
+}
+
+// trashed copies are left out, and groups that are down to one file
+pub fn prune_groups(tree: &Tree, groups: &mut Vec<DuplicateGroup>) {
+    for group in groups.iter_mut() {
+        group.nodes.retain(|(id, _)| tree.is_attached(*id));
+    }
+    groups.retain(|g| g.nodes.len() > 1);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::Node;
+    use std::fs;
+
+    // a directory of its own for each test, under the system's temporary directory
+    fn test_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("spaceman-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    fn file_node(path: PathBuf, size: u64) -> Node {
+        Node {
+            name: path.file_name().unwrap().to_string_lossy().into_owned(),
+            path,
+            size,
+            is_file: true,
+            ..Default::default()
+        }
+    }
+
+    // the files written to the directory, added to a tree the way the scan would
+    fn tree_of(dir: &Path, files: &[(&str, Vec<u8>)]) -> Tree {
+        let mut tree = Tree::new(&dir.to_string_lossy());
+        for (name, contents) in files {
+            let path = dir.join(name);
+            fs::write(&path, contents).unwrap();
+            tree.add_elem(0, file_node(path, contents.len() as u64));
+        }
+        tree
+    }
+
+    fn names(groups: &[DuplicateGroup]) -> Vec<Vec<String>> {
+        let mut names: Vec<Vec<String>> = groups
+            .iter()
+            .map(|g| {
+                let mut names: Vec<String> = g
+                    .nodes
+                    .iter()
+                    .map(|(_, p)| Path::new(p).file_name().unwrap().to_string_lossy().into())
+                    .collect();
+                names.sort();
+                names
+            })
+            .collect();
+        names.sort();
+        names
+    }
+
+    #[test]
+    fn grouped_by_size_start_and_contents() {
+        let dir = test_dir("duplicates");
+        let large = vec![7; PARTIAL_HASH_SIZE + 100];
+        let mut large_other_end = large.clone();
+        *large_other_end.last_mut().unwrap() = 8;
+        let tree = tree_of(
+            &dir,
+            &[
+                ("a", b"same".to_vec()),
+                ("b", b"same".to_vec()),
+                // the same size, other contents
+                ("c", b"diff".to_vec()),
+                ("d", b"longer".to_vec()),
+                ("e", vec![]),
+                ("f", vec![]),
+                ("g", large.clone()),
+                ("h", large),
+                // the same start, only the full hash tells it apart
+                ("i", large_other_end),
+            ],
+        );
+        let groups = group_duplicates(candidates(&tree));
+        assert_eq!(names(&groups), vec![vec!["a", "b"], vec!["g", "h"]]);
+        // the largest waste first
+        assert_eq!(groups[0].size, (PARTIAL_HASH_SIZE + 100) as u64);
+        assert_eq!(groups[0].wasted(), (PARTIAL_HASH_SIZE + 100) as u64);
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn contents_are_compared() {
+        let dir = test_dir("compare");
+        let tree = tree_of(
+            &dir,
+            &[
+                ("a", b"one".to_vec()),
+                ("b", b"two".to_vec()),
+                ("c", b"one".to_vec()),
+                ("d", b"two".to_vec()),
+            ],
+        );
+        // as if all four had the same hash
+        let group = candidates(&tree).into_iter().flatten().collect();
+        let split = split_by_contents(group);
+        let mut names: Vec<Vec<&str>> = split
+            .iter()
+            .map(|g| {
+                let mut names: Vec<&str> = g
+                    .iter()
+                    .map(|c| tree.get_elem(c.id).name.as_str())
+                    .collect();
+                names.sort();
+                names
+            })
+            .collect();
+        names.sort();
+        assert_eq!(names, vec![vec!["a", "c"], vec!["b", "d"]]);
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn hard_links_are_one_file() {
+        let mut tree = Tree::new("/");
+        for (name, ino) in [("a", 1), ("b", 1), ("c", 2), ("d", 3)] {
+            let mut node = file_node(PathBuf::from(name), 10);
+            node.dev = Some(1);
+            node.ino = Some(ino);
+            tree.add_elem(0, node);
+        }
+        let groups = candidates(&tree);
+        assert_eq!(groups.len(), 1);
+        let mut inodes: Vec<u64> = groups[0]
+            .iter()
+            .map(|c| tree.get_elem(c.id).ino.unwrap())
+            .collect();
+        inodes.sort();
+        assert_eq!(inodes, vec![1, 2, 3]);
+    }
+}
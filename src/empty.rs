@@ -0,0 +1,78 @@
+use crate::types::{NodeID, Tree};
+
+// what can go without losing any data
+// only the topmost empty directories are listed, the ones inside go with them
+#[derive(Debug, Clone, Default)]
+pub struct EmptyReport {
+    // with their paths for the panel, which can't lock the tree
+    pub dirs: Vec<(NodeID, String)>,
+    pub files: Vec<(NodeID, String)>,
+}
+
+impl EmptyReport {
+    pub fn node_ids(&self) -> Vec<NodeID> {
+        self.dirs
+            .iter()
+            .chain(self.files.iter())
+            .map(|(id, _)| *id)
+            .collect()
+    }
+}
+
+// directories with no files anywhere below them, and files of zero bytes
+// symlinks and mount points look empty to the scan, so they are left out, and so are
+// FIFOs, sockets and devices, and directories with something below them that couldn't
+// be read or was excluded
+pub fn find_empty(tree: &Tree, root: NodeID) -> EmptyReport {
+    let mut report = EmptyReport::default();
+    let mut stack = tree.get_elem(root).children.clone();
+    while let Some(id) = stack.pop() {
+        let node = tree.get_elem(id);
+        let path = || node.path.to_string_lossy().into_owned();
+        if node.is_symlink || node.is_special || tree.is_mount_point(id) {
+            continue;
+        }
+        if node.is_file {
+            if node.size == 0 {
+                report.files.push((id, path()));
+            }
+        } else if node.file_count == 0 && !node.incomplete {
+            report.dirs.push((id, path()));
+        } else {
+            stack.extend(node.children.iter());
+        }
+    }
+    report.dirs.sort_by(|a, b| a.1.cmp(&b.1));
+    report.files.sort_by(|a, b| a.1.cmp(&b.1));
+    report
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::Node;
+
+    fn node(name: &str, is_file: bool, is_special: bool) -> Node {
+        Node {
+            name: name.into(),
+            path: name.into(),
+            is_file,
+            is_special,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn special_files_are_not_empty() {
+        let mut tree = Tree::new("/");
+        tree.add_elem(0, node("fifo", false, true));
+        tree.add_elem(0, node("with_socket", false, false));
+        let dir = tree.last_id;
+        tree.add_elem(dir, node("socket", false, true));
+        tree.add_elem(0, node("empty", false, false));
+        tree.add_elem(0, node("zero", true, false));
+        let report = find_empty(&tree, 0);
+        assert_eq!(report.dirs, vec![(4, String::from("empty"))]);
+        assert_eq!(report.files, vec![(5, String::from("zero"))]);
+    }
+}
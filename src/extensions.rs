@@ -0,0 +1,88 @@
+use crate::types::{NodeID, Tree};
+use std::collections::HashMap;
+use std::fmt;
+
+// the bytes and number of the files with one extension
+#[derive(Debug, Clone)]
+pub struct ExtensionStats {
+    // lowercase without the dot, empty for files without one
+    pub extension: String,
+    pub bytes: u64,
+    pub count: u64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ExtensionColumn {
+    Extension,
+    #[default]
+    Bytes,
+    Files,
+}
+
+impl fmt::Display for ExtensionColumn {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ExtensionColumn::Extension => write!(f, "Extension"),
+            ExtensionColumn::Bytes => write!(f, "Size"),
+            ExtensionColumn::Files => write!(f, "Files"),
+        }
+    }
+}
+
+// hidden files like .bashrc have no extension, the dot only marks them hidden
+pub fn extension_of(name: &str) -> String {
+    match name.rsplit_once('.') {
+        Some((stem, extension)) if !stem.is_empty() => extension.to_lowercase(),
+        _ => String::new(),
+    }
+}
+
+fn files_under(tree: &Tree, root: NodeID) -> impl Iterator<Item = NodeID> + '_ {
+    let mut stack = vec![root];
+    std::iter::from_fn(move || {
+        while let Some(id) = stack.pop() {
+            let node = tree.get_elem(id);
+            if node.is_file {
+                return Some(id);
+            }
+            stack.extend(node.children.iter());
+        }
+        None
+    })
+}
+
+pub fn extension_stats(tree: &Tree, root: NodeID) -> Vec<ExtensionStats> {
+    let mut stats: HashMap<String, ExtensionStats> = HashMap::new();
+    for id in files_under(tree, root) {
+        let node = tree.get_elem(id);
+        let extension = extension_of(&node.name);
+        let entry = stats
+            .entry(extension.clone())
+            .or_insert_with(|| ExtensionStats {
+                extension,
+                bytes: 0,
+                count: 0,
+            });
+        entry.bytes += node.size;
+        entry.count += 1;
+    }
+    stats.into_values().collect()
+}
+
+// the files under root with the extension, to mark them in the treemap
+pub fn files_with_extension(tree: &Tree, root: NodeID, extension: &str) -> Vec<NodeID> {
+    files_under(tree, root)
+        .filter(|id| extension_of(&tree.get_elem(*id).name) == extension)
+        .collect()
+}
+
+pub fn sort_stats(stats: &mut [ExtensionStats], column: ExtensionColumn, descending: bool) {
+    match column {
+        ExtensionColumn::Extension => stats.sort_by(|a, b| a.extension.cmp(&b.extension)),
+        ExtensionColumn::Bytes => stats.sort_by_key(|s| s.bytes),
+        ExtensionColumn::Files => stats.sort_by_key(|s| s.count),
+    }
+    if descending {
+        stats.reverse();
+    }
+}
@@ -0,0 +1,547 @@
+use crate::types::{GitStatus, NodeID, Tree};
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+// one line of a .gitignore file
+// https://git-scm.com/docs/gitignore#_pattern_format
+struct IgnorePattern {
+    glob: Vec<u8>,
+    negated: bool,
+    dir_only: bool,
+    // a slash at the start or in the middle ties the pattern to the directory of the file
+    anchored: bool,
+}
+
+impl IgnorePattern {
+    fn parse(line: &str) -> Option<Self> {
+        let line = line.trim_end();
+        if line.is_empty() || line.starts_with('#') {
+            return None;
+        }
+        let (negated, line) = match line.strip_prefix('!') {
+            Some(rest) => (true, rest),
+            None => (false, line.strip_prefix('\\').unwrap_or(line)),
+        };
+        let (dir_only, line) = match line.strip_suffix('/') {
+            Some(rest) => (true, rest),
+            None => (false, line),
+        };
+        let anchored = line.contains('/');
+        let glob = line.strip_prefix('/').unwrap_or(line);
+        if glob.is_empty() {
+            return None;
+        }
+        Some(Self {
+            glob: glob.as_bytes().to_vec(),
+            negated,
+            dir_only,
+            anchored,
+        })
+    }
+
+    // path is relative to the directory of the .gitignore file
+    fn matches(&self, path: &str, is_dir: bool) -> bool {
+        if self.dir_only && !is_dir {
+            return false;
+        }
+        if self.anchored {
+            glob_match(&self.glob, path.as_bytes())
+        } else {
+            let name = path.rsplit('/').next().unwrap_or(path);
+            glob_match(&self.glob, name.as_bytes())
+        }
+    }
+}
+
+// * and ? don't match a slash, ** matches anything, **/ also matches no directory at all
+pub fn glob_match(pattern: &[u8], s: &[u8]) -> bool {
+    match pattern.first() {
+        None => s.is_empty(),
+        Some(b'*') if pattern.get(1) == Some(&b'*') => {
+            let rest = &pattern[2..];
+            match rest.strip_prefix(b"/") {
+                Some(rest) => {
+                    glob_match(rest, s)
+                        || (0..s.len()).any(|i| s[i] == b'/' && glob_match(rest, &s[i + 1..]))
+                }
+                None => (0..=s.len()).any(|i| glob_match(rest, &s[i..])),
+            }
+        }
+        Some(b'*') => {
+            for i in 0..=s.len() {
+                if glob_match(&pattern[1..], &s[i..]) {
+                    return true;
+                }
+                if i < s.len() && s[i] == b'/' {
+                    break;
+                }
+            }
+            false
+        }
+        Some(b'?') => !s.is_empty() && s[0] != b'/' && glob_match(&pattern[1..], &s[1..]),
+        Some(b'[') => match (s.first(), class_match(&pattern[1..], s.first().copied())) {
+            (Some(_), Some((true, rest))) => glob_match(rest, &s[1..]),
+            (_, None) => s.first() == Some(&b'[') && glob_match(&pattern[1..], &s[1..]),
+            _ => false,
+        },
+        Some(b'\\') if pattern.len() > 1 => {
+            s.first() == Some(&pattern[1]) && glob_match(&pattern[2..], &s[1..])
+        }
+        Some(c) => s.first() == Some(c) && glob_match(&pattern[1..], &s[1..]),
+    }
+}
+
+// [abc], [a-z] and [!abc], returns whether c is in the class and the pattern after it
+// None when the class isn't closed, the [ is taken literally then
+fn class_match(pattern: &[u8], c: Option<u8>) -> Option<(bool, &[u8])> {
+    let (negated, mut i) = match pattern.first() {
+        Some(b'!') | Some(b'^') => (true, 1),
+        _ => (false, 0),
+    };
+    let mut found = false;
+    let mut first = true;
+    while i < pattern.len() {
+        let start = pattern[i];
+        if start == b']' && !first {
+            return Some((found != negated && c != Some(b'/'), &pattern[i + 1..]));
+        }
+        first = false;
+        if pattern.get(i + 1) == Some(&b'-') && pattern.get(i + 2).is_some_and(|e| *e != b']') {
+            let end = pattern[i + 2];
+            found |= c.is_some_and(|c| c >= start && c <= end);
+            i += 3;
+        } else {
+            found |= c == Some(start);
+            i += 1;
+        }
+    }
+    None
+}
+
+// the patterns of one .gitignore, and the directory it's in relative to the repository
+struct IgnoreFile {
+    base: String,
+    patterns: Vec<IgnorePattern>,
+}
+
+impl IgnoreFile {
+    fn read(path: &Path, base: String) -> Self {
+        let patterns = fs::read_to_string(path)
+            .unwrap_or_default()
+            .lines()
+            .filter_map(IgnorePattern::parse)
+            .collect();
+        Self { base, patterns }
+    }
+
+    // Some(ignored) when a pattern matches, the last one wins
+    fn check(&self, path: &str, is_dir: bool) -> Option<bool> {
+        let path = if self.base.is_empty() {
+            path
+        } else {
+            path.strip_prefix(&self.base)?.strip_prefix('/')?
+        };
+        self.patterns
+            .iter()
+            .rev()
+            .find(|p| p.matches(path, is_dir))
+            .map(|p| !p.negated)
+    }
+}
+
+// https://git-scm.com/docs/index-format
+// only the paths are read, for versions 2 to 4
+fn read_index(git_dir: &Path) -> HashSet<String> {
+    fs::read(git_dir.join("index"))
+        .map(|data| index_paths(&data))
+        .unwrap_or_default()
+}
+
+fn index_paths(data: &[u8]) -> HashSet<String> {
+    let mut paths = HashSet::new();
+    if data.len() < 12 || &data[0..4] != b"DIRC" {
+        return paths;
+    }
+    let read_u32 =
+        |at: usize| u32::from_be_bytes([data[at], data[at + 1], data[at + 2], data[at + 3]]);
+    let version = read_u32(4);
+    let count = read_u32(8);
+    let mut at = 12;
+    let mut previous: Vec<u8> = vec![];
+    for _ in 0..count {
+        // timestamps, stat data and the object id come before the flags
+        let start = at;
+        at += 60;
+        if at + 2 > data.len() {
+            break;
+        }
+        let flags = u16::from_be_bytes([data[at], data[at + 1]]);
+        at += 2;
+        if version >= 3 && flags & 0x4000 != 0 {
+            at += 2;
+        }
+        let mut path = if version >= 4 {
+            // the number of bytes to drop from the end of the previous path
+            let Some((strip, len)) = read_varint(&data[at.min(data.len())..]) else {
+                break;
+            };
+            at += len;
+            let mut path = previous.clone();
+            path.truncate(path.len().saturating_sub(strip));
+            path
+        } else {
+            vec![]
+        };
+        let Some(end) = data[at.min(data.len())..].iter().position(|b| *b == 0) else {
+            break;
+        };
+        path.extend_from_slice(&data[at..at + end]);
+        at += end + 1;
+        if version < 4 {
+            // entries are padded with NULs to a multiple of 8 bytes
+            at = start + (at - start).div_ceil(8) * 8;
+        }
+        paths.insert(String::from_utf8_lossy(&path).into_owned());
+        previous = path;
+    }
+    paths
+}
+
+// the offset encoding of git, returns the value and the bytes it took
+// None when it runs past the data or doesn't fit
+fn read_varint(data: &[u8]) -> Option<(usize, usize)> {
+    let mut i = 0;
+    let mut byte = *data.get(i)?;
+    let mut value = (byte & 0x7f) as usize;
+    while byte & 0x80 != 0 {
+        i += 1;
+        byte = *data.get(i)?;
+        value = value.checked_add(1)?.checked_mul(1 << 7)? | (byte & 0x7f) as usize;
+    }
+    Some((value, i + 1))
+}
+
+// .git is usually a directory, worktrees and submodules have a file pointing to it instead
+fn git_dir(worktree: &Path) -> PathBuf {
+    let dot_git = worktree.join(".git");
+    match fs::read_to_string(&dot_git) {
+        Ok(contents) => match contents.trim().strip_prefix("gitdir:") {
+            Some(dir) => worktree.join(dir.trim()),
+            None => dot_git,
+        },
+        Err(_) => dot_git,
+    }
+}
+
+fn is_repo(tree: &Tree, id: NodeID) -> bool {
+    tree.get_elem(id)
+        .children
+        .iter()
+        .any(|c| tree.get_elem(*c).name == ".git")
+}
+
+// what has to be read from the disk for a repository
+struct RepoFiles {
+    root: NodeID,
+    git_dir: PathBuf,
+    // directories with a .gitignore in them, relative to the repository
+    gitignores: Vec<(PathBuf, String)>,
+}
+
+// the nodes of a repository with their path relative to it, not going into .git
+// or into the other repositories inside of it
+fn repo_nodes(tree: &Tree, root: NodeID) -> Vec<(NodeID, String)> {
+    let mut nodes = vec![];
+    let mut stack = vec![(root, String::new())];
+    while let Some((id, path)) = stack.pop() {
+        for child in tree.get_elem(id).children.iter() {
+            let node = tree.get_elem(*child);
+            if node.name == ".git" || (!node.is_file && is_repo(tree, *child)) {
+                continue;
+            }
+            let child_path = if path.is_empty() {
+                node.name.clone()
+            } else {
+                format!("{}/{}", path, node.name)
+            };
+            if !node.is_file {
+                stack.push((*child, child_path.clone()));
+            }
+            nodes.push((*child, child_path));
+        }
+    }
+    nodes
+}
+
+// sets the status of every file in the repositories found by the scan
+// the tree is only locked while it's looked at, not while files are read
+pub fn classify_repos(tree_mutex: &Mutex<Tree>) {
+    let repos: Vec<RepoFiles> = if let Ok(tree) = tree_mutex.lock() {
+        tree.git_repos
+            .iter()
+            .map(|root| {
+                let worktree = &tree.get_elem(*root).path;
+                let mut gitignores = vec![];
+                for (id, path) in repo_nodes(&tree, *root) {
+                    let node = tree.get_elem(id);
+                    if node.is_file && node.name == ".gitignore" {
+                        let dir = path.rsplit_once('/').map(|(dir, _)| dir).unwrap_or("");
+                        gitignores.push((node.path.clone(), dir.to_string()));
+                    }
+                }
+                RepoFiles {
+                    root: *root,
+                    git_dir: git_dir(worktree),
+                    gitignores,
+                }
+            })
+            .collect()
+    } else {
+        return;
+    };
+
+    for repo in repos {
+        let tracked = read_index(&repo.git_dir);
+        let exclude = IgnoreFile::read(&repo.git_dir.join("info").join("exclude"), String::new());
+        let gitignores: HashMap<String, IgnoreFile> = repo
+            .gitignores
+            .into_iter()
+            .map(|(path, base)| (base.clone(), IgnoreFile::read(&path, base)))
+            .collect();
+
+        if let Ok(mut tree) = tree_mutex.lock() {
+            // parents come before their children, so their status is known when a child is checked
+            let mut ignored_dirs: HashSet<String> = HashSet::new();
+            let nodes = repo_nodes(&tree, repo.root);
+            for (id, path) in nodes {
+                let (dir, _) = path.rsplit_once('/').unwrap_or(("", &path));
+                let is_file = tree.get_elem(id).is_file;
+                // nothing in an ignored directory can be taken back in
+                let ignored = ignored_dirs.contains(dir)
+                    || is_ignored(&path, !is_file, &gitignores, &exclude);
+                if !is_file {
+                    if ignored {
+                        ignored_dirs.insert(path);
+                    }
+                    continue;
+                }
+                let status = if tracked.contains(&path) {
+                    GitStatus::Tracked
+                } else if ignored {
+                    GitStatus::Ignored
+                } else {
+                    GitStatus::Untracked
+                };
+                tree.set_git_status(id, status);
+            }
+        }
+    }
+}
+
+// the .gitignore closest to the path decides, then the ones above it, then info/exclude
+fn is_ignored(
+    path: &str,
+    is_dir: bool,
+    gitignores: &HashMap<String, IgnoreFile>,
+    exclude: &IgnoreFile,
+) -> bool {
+    let mut dir = path;
+    loop {
+        dir = dir.rsplit_once('/').map(|(dir, _)| dir).unwrap_or("");
+        if let Some(ignored) = gitignores.get(dir).and_then(|f| f.check(path, is_dir)) {
+            return ignored;
+        }
+        if dir.is_empty() {
+            break;
+        }
+    }
+    exclude.check(path, is_dir).unwrap_or(false)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pattern(line: &str) -> IgnorePattern {
+        IgnorePattern::parse(line).unwrap()
+    }
+
+    #[test]
+    fn star_stays_in_one_directory() {
+        assert!(glob_match(b"*.rs", b"main.rs"));
+        assert!(!glob_match(b"*.rs", b"src/main.rs"));
+        assert!(glob_match(b"a?c", b"abc"));
+        assert!(!glob_match(b"a?c", b"a/c"));
+        assert!(glob_match(b"\\*", b"*"));
+        assert!(!glob_match(b"\\*", b"a"));
+    }
+
+    #[test]
+    fn double_star() {
+        assert!(glob_match(b"**/target", b"target"));
+        assert!(glob_match(b"**/target", b"a/b/target"));
+        assert!(glob_match(b"src/**", b"src/a/b.rs"));
+        assert!(glob_match(b"a/**/b", b"a/b"));
+        assert!(glob_match(b"a/**/b", b"a/x/y/b"));
+        assert!(!glob_match(b"a/**/b", b"ab"));
+    }
+
+    #[test]
+    fn bracket_classes() {
+        assert!(glob_match(b"[abc].txt", b"b.txt"));
+        assert!(!glob_match(b"[abc].txt", b"d.txt"));
+        assert!(glob_match(b"file[0-9]", b"file7"));
+        assert!(!glob_match(b"file[0-9]", b"filex"));
+        assert!(glob_match(b"[!a]x", b"bx"));
+        assert!(!glob_match(b"[!a]x", b"ax"));
+        assert!(!glob_match(b"[!a]", b"/"));
+        // a ] right after the [ and a - at the end are taken literally
+        assert!(glob_match(b"[]]", b"]"));
+        assert!(glob_match(b"[a-]", b"-"));
+        // so is a [ that isn't closed
+        assert!(glob_match(b"[ab", b"[ab"));
+        assert_eq!(
+            class_match(b"a-c]rest", Some(b'b')),
+            Some((true, &b"rest"[..]))
+        );
+        assert_eq!(class_match(b"abc", Some(b'a')), None);
+    }
+
+    #[test]
+    fn ignore_patterns() {
+        assert!(IgnorePattern::parse("# comment").is_none());
+        assert!(IgnorePattern::parse("").is_none());
+        assert!(pattern("\\#file").matches("#file", false));
+        // without a slash before the end the name is matched at any depth
+        assert!(pattern("*.log").matches("a/b/debug.log", false));
+        // with one the pattern is tied to the directory of the .gitignore
+        assert!(pattern("/build").matches("build", true));
+        assert!(!pattern("/build").matches("src/build", true));
+        assert!(pattern("doc/*.txt").matches("doc/a.txt", false));
+        assert!(!pattern("doc/*.txt").matches("doc/x/a.txt", false));
+        // a slash at the end only matches directories
+        assert!(pattern("target/").matches("target", true));
+        assert!(!pattern("target/").matches("target", false));
+    }
+
+    #[test]
+    fn negation_and_nesting() {
+        let file = IgnoreFile {
+            base: String::new(),
+            patterns: vec![pattern("*.log"), pattern("!keep.log")],
+        };
+        assert_eq!(file.check("debug.log", false), Some(true));
+        assert_eq!(file.check("keep.log", false), Some(false));
+        assert_eq!(file.check("main.rs", false), None);
+
+        let nested = IgnoreFile {
+            base: String::from("sub"),
+            patterns: vec![pattern("/*.tmp")],
+        };
+        assert_eq!(nested.check("sub/a.tmp", false), Some(true));
+        assert_eq!(nested.check("sub/x/a.tmp", false), None);
+        assert_eq!(nested.check("a.tmp", false), None);
+
+        let gitignores = HashMap::from([(String::from("sub"), nested)]);
+        assert!(is_ignored("sub/a.tmp", false, &gitignores, &file));
+        assert!(is_ignored("sub/debug.log", false, &gitignores, &file));
+        assert!(!is_ignored("sub/keep.log", false, &gitignores, &file));
+    }
+
+    #[test]
+    fn varints() {
+        assert_eq!(read_varint(&[0x05]), Some((5, 1)));
+        assert_eq!(read_varint(&[0x81, 0x00, 0xff]), Some((256, 2)));
+        assert_eq!(read_varint(&[0x80]), None);
+        assert_eq!(read_varint(&[0xff; 12]), None);
+    }
+
+    // an entry with zeroed stat data and object id, the extended flags of version 3,
+    // and the path or in version 4 how much of the previous one to drop and what follows
+    fn index_entry(version: u32, strip: u8, path: &str, extended: bool) -> Vec<u8> {
+        let mut entry = vec![0; 60];
+        let flags = path.len() as u16 | if extended { 0x4000 } else { 0 };
+        entry.extend_from_slice(&flags.to_be_bytes());
+        if extended {
+            entry.extend_from_slice(&[0, 0]);
+        }
+        if version >= 4 {
+            entry.push(strip);
+        }
+        entry.extend_from_slice(path.as_bytes());
+        entry.push(0);
+        if version < 4 {
+            while !entry.len().is_multiple_of(8) {
+                entry.push(0);
+            }
+        }
+        entry
+    }
+
+    fn index(version: u32, entries: &[Vec<u8>]) -> Vec<u8> {
+        let mut data = b"DIRC".to_vec();
+        data.extend_from_slice(&version.to_be_bytes());
+        data.extend_from_slice(&(entries.len() as u32).to_be_bytes());
+        for entry in entries {
+            data.extend_from_slice(entry);
+        }
+        data
+    }
+
+    fn paths(names: &[&str]) -> HashSet<String> {
+        names.iter().map(|n| n.to_string()).collect()
+    }
+
+    #[test]
+    fn index_v2() {
+        let data = index(
+            2,
+            &[
+                index_entry(2, 0, "README.md", false),
+                index_entry(2, 0, "src/main.rs", false),
+            ],
+        );
+        assert_eq!(index_paths(&data), paths(&["README.md", "src/main.rs"]));
+    }
+
+    #[test]
+    fn index_v3_extended_flags() {
+        let data = index(
+            3,
+            &[
+                index_entry(3, 0, "a.txt", true),
+                index_entry(3, 0, "b/c.txt", false),
+            ],
+        );
+        assert_eq!(index_paths(&data), paths(&["a.txt", "b/c.txt"]));
+    }
+
+    #[test]
+    fn index_v4_prefix_compression() {
+        let data = index(
+            4,
+            &[
+                index_entry(4, 0, "src/git.rs", false),
+                index_entry(4, 6, "main.rs", false),
+                index_entry(4, 7, "ui/mod.rs", false),
+            ],
+        );
+        assert_eq!(
+            index_paths(&data),
+            paths(&["src/git.rs", "src/main.rs", "src/ui/mod.rs"])
+        );
+    }
+
+    #[test]
+    fn broken_index() {
+        assert!(index_paths(b"not an index").is_empty());
+        // the entries that are there are read, the missing ones don't panic
+        let mut data = index(2, &[index_entry(2, 0, "a.txt", false)]);
+        data[11] = 3;
+        assert_eq!(index_paths(&data), paths(&["a.txt"]));
+        data.truncate(data.len() - 8);
+        assert!(index_paths(&data).is_empty());
+    }
+}
@@ -0,0 +1,88 @@
+use crate::{
+    config::{self, MIN_ICICLE_WIDTH, MIN_ROW_HEIGHT},
+    node_color::Colorizer,
+    sizes::Sizes,
+    types::{GUINode, NodeID, Rectangle, Tree},
+};
+
+// deepest level below root that has something to show, capped at max_visible_fs_depth
+fn visible_depth(tree: &Tree, root_id: NodeID, sizes: &Sizes) -> usize {
+    let max_depth = config::tuning().max_visible_fs_depth;
+    let mut max_level = 0;
+    let mut stack = vec![(root_id, 0)];
+    while let Some((id, level)) = stack.pop() {
+        max_level = max_level.max(level);
+        if level < max_depth {
+            stack.extend(
+                tree.get_elem(id)
+                    .children
+                    .iter()
+                    .filter(|c| sizes.is_shown(tree.get_elem(**c)))
+                    .map(|c| (*c, level + 1)),
+            );
+        }
+    }
+    max_level
+}
+
+// one row per level of depth, widths proportional to size
+pub fn compute_gui_nodes(
+    tree: &Tree,
+    root_id: NodeID,
+    bound: Rectangle,
+    colorizer: &Colorizer,
+    sizes: &Sizes,
+) -> Vec<GUINode> {
+    let size = |id: NodeID| sizes.of(tree.get_elem(id));
+    let rows = visible_depth(tree, root_id, sizes) + 1;
+    let row_height = (bound.height / rows as f32).max(MIN_ROW_HEIGHT);
+
+    let max_depth = config::tuning().max_visible_fs_depth;
+    let mut gui_nodes = vec![];
+    // (node, level, x, width)
+    let mut stack = vec![(root_id, 0, bound.x, bound.width)];
+    while let Some((id, level, x, width)) = stack.pop() {
+        let y = bound.y + level as f32 * row_height;
+        if y + row_height > bound.y + bound.height + 0.5 {
+            continue;
+        }
+        let node = tree.get_elem(id);
+        gui_nodes.push(GUINode {
+            rect: Rectangle {
+                x,
+                y,
+                width,
+                height: row_height,
+            },
+            node_id: id,
+            is_file: node.is_file,
+            color: colorizer.color(node),
+            label: sizes.label(node),
+            small_items: vec![],
+        });
+
+        let total = size(id);
+        if level >= max_depth || total == 0 {
+            continue;
+        }
+        let mut children: Vec<NodeID> = node
+            .children
+            .iter()
+            .copied()
+            .filter(|c| size(*c) > 0)
+            .collect();
+        children.sort_by_key(|c| std::cmp::Reverse(size(*c)));
+
+        let mut child_x = x;
+        for child in children {
+            let child_width = width * (size(child) as f32 / total as f32);
+            // the children are sorted, the rest will be even narrower
+            if child_width < MIN_ICICLE_WIDTH {
+                break;
+            }
+            stack.push((child, level + 1, child_x, child_width));
+            child_x += child_width;
+        }
+    }
+    gui_nodes
+}
@@ -0,0 +1,85 @@
+use crate::types::NodeID;
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
+
+// the k largest files seen so far, kept up to date while scanning
+// the smallest of them is on top of the heap, to be pushed out by a larger one
+#[derive(Debug)]
+pub struct LargestFiles {
+    k: usize,
+    heap: BinaryHeap<Reverse<(u64, NodeID)>>,
+}
+
+impl LargestFiles {
+    pub fn new(k: usize) -> Self {
+        Self {
+            k,
+            heap: BinaryHeap::with_capacity(k + 1),
+        }
+    }
+
+    pub fn offer(&mut self, size: u64, id: NodeID) {
+        if self.heap.len() < self.k {
+            self.heap.push(Reverse((size, id)));
+        } else if self
+            .heap
+            .peek()
+            .is_some_and(|Reverse(smallest)| smallest.0 < size)
+        {
+            self.heap.pop();
+            self.heap.push(Reverse((size, id)));
+        }
+    }
+
+    // largest first
+    pub fn sorted(&self) -> Vec<(u64, NodeID)> {
+        let mut files: Vec<(u64, NodeID)> = self.heap.iter().map(|Reverse(f)| *f).collect();
+        files.sort_by_key(|f| Reverse(*f));
+        files
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::LARGEST_FILES;
+    use crate::types::{Node, Tree};
+
+    #[test]
+    fn keeps_the_largest() {
+        let mut largest = LargestFiles::new(3);
+        for (id, size) in [5, 1, 9, 3, 7, 9].into_iter().enumerate() {
+            largest.offer(size, id);
+        }
+        assert_eq!(largest.sorted(), vec![(9, 5), (9, 2), (7, 4)]);
+    }
+
+    #[test]
+    fn refilled_after_trashing() {
+        let mut tree = Tree::new("/");
+        // one more file than the heap holds, the smallest is pushed out
+        for size in 1..=LARGEST_FILES as u64 + 1 {
+            tree.add_elem(
+                0,
+                Node {
+                    size,
+                    is_file: true,
+                    ..Default::default()
+                },
+            );
+            let id = tree.last_id;
+            tree.largest_files.offer(size, id);
+        }
+        assert_eq!(tree.largest_files.sorted().len(), LARGEST_FILES);
+        assert_eq!(tree.largest_files.sorted().last(), Some(&(2, 2)));
+
+        let largest = tree.last_id;
+        tree.invalidate_elem(largest);
+        tree.refill_largest_files();
+        let sorted = tree.largest_files.sorted();
+        assert_eq!(sorted.len(), LARGEST_FILES);
+        assert_eq!(sorted.first(), Some(&(LARGEST_FILES as u64, LARGEST_FILES)));
+        // the file pushed out earlier takes the trashed one's place
+        assert_eq!(sorted.last(), Some(&(1, 1)));
+    }
+}
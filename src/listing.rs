@@ -0,0 +1,109 @@
+use crate::sizes::Sizes;
+use crate::types::{NodeID, Tree};
+use std::fmt;
+
+// a row of the list panel, one per child of the shown root
+#[derive(Debug, Clone)]
+pub struct ListRow {
+    pub node_id: NodeID,
+    pub name: String,
+    pub is_file: bool,
+    // in the unit of the size mode, see sizes::SizeMode::display
+    pub size: u64,
+    // share of the shown root's size, 0.0 to 1.0
+    pub fraction: f32,
+    // everything below the node, the node itself not included
+    pub items: u64,
+    // newest modification time for directories
+    pub mtime: Option<u64>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SortColumn {
+    Name,
+    #[default]
+    Size,
+    Items,
+    Modified,
+}
+
+impl fmt::Display for SortColumn {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SortColumn::Name => write!(f, "Name"),
+            SortColumn::Size => write!(f, "Size"),
+            SortColumn::Items => write!(f, "Items"),
+            SortColumn::Modified => write!(f, "Modified"),
+        }
+    }
+}
+
+// the tree keeps counts, but a filtered view has to count what's left
+fn count_items(tree: &Tree, id: NodeID, sizes: &Sizes) -> u64 {
+    let node = tree.get_elem(id);
+    if sizes.values.is_none() {
+        return node.file_count + node.dir_count;
+    }
+    let mut count = 0;
+    let mut stack = node.children.clone();
+    while let Some(id) = stack.pop() {
+        if !sizes.is_shown(tree.get_elem(id)) {
+            continue;
+        }
+        count += 1;
+        stack.extend(&tree.get_elem(id).children);
+    }
+    count
+}
+
+pub fn list_children(tree: &Tree, root_id: NodeID, sizes: &Sizes) -> Vec<ListRow> {
+    let children: Vec<NodeID> = tree
+        .get_elem(root_id)
+        .children
+        .iter()
+        .copied()
+        .filter(|c| sizes.is_shown(tree.get_elem(*c)))
+        .collect();
+    list_nodes(tree, &children, root_id, sizes)
+}
+
+// rows for some of the children of parent_id, their fractions are of the parent's size
+pub fn list_nodes(tree: &Tree, ids: &[NodeID], parent_id: NodeID, sizes: &Sizes) -> Vec<ListRow> {
+    let size = |id: NodeID| sizes.of(tree.get_elem(id));
+    let total = size(parent_id);
+    ids.iter()
+        .map(|&id| {
+            let node = tree.get_elem(id);
+            ListRow {
+                node_id: id,
+                name: node.name.clone(),
+                is_file: node.is_file,
+                size: size(id),
+                fraction: if total > 0 {
+                    size(id) as f32 / total as f32
+                } else {
+                    0.0
+                },
+                items: count_items(tree, id, sizes),
+                mtime: if node.is_file {
+                    node.mtime
+                } else {
+                    node.newest_mtime
+                },
+            }
+        })
+        .collect()
+}
+
+// descending puts the largest, the most items and the newest first, names go z to a
+pub fn sort_rows(rows: &mut [ListRow], column: SortColumn, descending: bool) {
+    match column {
+        SortColumn::Name => rows.sort_by_cached_key(|r| r.name.to_lowercase()),
+        SortColumn::Size => rows.sort_by_key(|r| r.size),
+        SortColumn::Items => rows.sort_by_key(|r| r.items),
+        SortColumn::Modified => rows.sort_by_key(|r| r.mtime),
+    }
+    if descending {
+        rows.reverse();
+    }
+}
@@ -1,24 +1,70 @@
 // prevent a command line window on Windows
 #![windows_subsystem = "windows"]
 mod actions;
+mod cli;
 mod config;
+mod cushion;
+mod details;
+mod duplicates;
+mod empty;
+mod extensions;
+mod git;
+mod icicle;
+mod largest;
+mod listing;
 //mod mounts;
 mod node_color;
+mod old;
+mod owners;
+mod reclaimable;
 mod scan;
+mod settings;
+mod sizes;
 mod squarify;
+mod state;
+mod sunburst;
+mod theme;
 mod types;
 mod ui;
 mod utils;
 
-use std::path::PathBuf;
+// a windows subsystem program has no console, the reports and errors are printed to the one
+// it was started from
+#[cfg(windows)]
+fn attach_console() {
+    const ATTACH_PARENT_PROCESS: u32 = u32::MAX;
+    unsafe extern "system" {
+        fn AttachConsole(process_id: u32) -> i32;
+    }
+    unsafe {
+        AttachConsole(ATTACH_PARENT_PROCESS);
+    }
+}
+
+#[cfg(not(windows))]
+fn attach_console() {}
 
 fn main() {
     //dbg!(mounts::get_mounts());
-    let args: Vec<_> = std::env::args().collect();
-    if args.len() > 1 {
-        ui::init(Some(PathBuf::from(&args[1])))
-    } else {
-        ui::init(None)
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let command = cli::parse(&args);
+    if !matches!(command, Ok(cli::Command::Gui(_))) {
+        attach_console();
+    }
+    match command {
+        Ok(cli::Command::Gui(path)) => ui::init(path).expect("Failed to initiate UI"),
+        Ok(cli::Command::Report(report, path, json)) => {
+            if let Err(e) = cli::run(report, &path, json) {
+                eprintln!("{}", e);
+                std::process::exit(1);
+            }
+        }
+        Err(e) => {
+            if !e.is_empty() {
+                eprintln!("{}", e);
+            }
+            eprintln!("{}", cli::USAGE);
+            std::process::exit(2);
+        }
     }
-    .expect("Failed to initiate UI");
 }
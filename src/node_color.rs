@@ -1,17 +1,9 @@
-use crate::types::RGBA;
-use once_cell::sync::Lazy;
+use crate::theme::ThemeColors;
+use crate::types::{GitStatus, Node, RGBA};
+use serde::{Deserialize, Serialize};
 use std::f32::consts::PI;
-
-// https://www.schemecolor.com/light-to-dark-blue.php
-// light blue #b6d4f2
-const DIR_R: f32 = 0xb6 as f32 / 256.0;
-const DIR_G: f32 = 0xd4 as f32 / 256.0;
-const DIR_B: f32 = 0xf2 as f32 / 256.0;
-
-// pink color #f4b9d1
-const FILE_R: f32 = 0xf4 as f32 / 256.0;
-const FILE_G: f32 = 0xb9 as f32 / 256.0;
-const FILE_B: f32 = 0xd1 as f32 / 256.0;
+use std::fmt;
+use std::time::{SystemTime, UNIX_EPOCH};
 
 struct HSL {
     hue: f32,
@@ -78,10 +70,11 @@ fn darken(ratio: f32, color: (f32, f32, f32)) -> (f32, f32, f32) {
     hsl.to_rgb()
 }
 
-static DIR: Lazy<[RGBA; 5]> = Lazy::new(|| {
+// the base color getting darker with each level of depth, cycling every 5 levels
+pub fn depth_palette(base: RGBA, ratio: f32) -> [RGBA; 5] {
     (0..5)
         .map(|depth| {
-            let color = darken(0.87_f32.powi(depth as i32), (DIR_R, DIR_G, DIR_B));
+            let color = darken(ratio.powi(depth), (base.r, base.g, base.b));
             RGBA {
                 r: color.0,
                 g: color.1,
@@ -92,28 +85,351 @@ static DIR: Lazy<[RGBA; 5]> = Lazy::new(|| {
         .collect::<Vec<RGBA>>()
         .try_into()
         .unwrap()
-});
+}
 
-static FILE: Lazy<[RGBA; 5]> = Lazy::new(|| {
-    (0..5)
-        .map(|depth| {
-            let color = darken(0.87_f32.powi(depth as i32), (FILE_R, FILE_G, FILE_B));
-            RGBA {
-                r: color.0,
-                g: color.1,
-                b: color.2,
-                a: 1.0,
-            }
-        })
-        .collect::<Vec<RGBA>>()
-        .try_into()
-        .unwrap()
-});
+pub const fn hex_color(hex: u32) -> RGBA {
+    RGBA {
+        r: ((hex >> 16) & 0xff) as f32 / 256.0,
+        g: ((hex >> 8) & 0xff) as f32 / 256.0,
+        b: (hex & 0xff) as f32 / 256.0,
+        a: 1.0,
+    }
+}
+
+// hue to use for the same lightness and saturation, in degrees
+fn with_hue(color: RGBA, hue: f32) -> RGBA {
+    let mut hsl = HSL::new((color.r, color.g, color.b));
+    hsl.hue = hue.to_radians();
+    let (r, g, b) = hsl.to_rgb();
+    RGBA {
+        r,
+        g,
+        b,
+        a: color.a,
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Vision {
+    #[default]
+    Normal,
+    Deuteranopia,
+    Protanopia,
+    Tritanopia,
+}
+
+impl Vision {
+    pub const ALL: [Vision; 4] = [
+        Vision::Normal,
+        Vision::Deuteranopia,
+        Vision::Protanopia,
+        Vision::Tritanopia,
+    ];
+
+    // directory and file hues replacing the theme's blue and pink
+    fn depth_hues(&self) -> Option<(f32, f32)> {
+        match self {
+            Vision::Normal => None,
+            // blue and orange stay apart without the red-green axis
+            Vision::Deuteranopia | Vision::Protanopia => Some((215.0, 30.0)),
+            // red and teal stay apart without the blue-yellow axis
+            Vision::Tritanopia => Some((180.0, 350.0)),
+        }
+    }
+
+    fn age_colors(&self) -> &'static [RGBA; 7] {
+        match self {
+            Vision::Normal => &AGE_COLORS,
+            Vision::Deuteranopia | Vision::Protanopia => &AGE_COLORS_RED_GREEN_SAFE,
+            Vision::Tritanopia => &AGE_COLORS_BLUE_YELLOW_SAFE,
+        }
+    }
+
+    fn owner_colors(&self) -> &'static [RGBA] {
+        match self {
+            Vision::Normal => &OWNER_COLORS,
+            Vision::Deuteranopia | Vision::Protanopia => &OWNER_COLORS_RED_GREEN_SAFE,
+            Vision::Tritanopia => &OWNER_COLORS_BLUE_YELLOW_SAFE,
+        }
+    }
+}
+
+impl fmt::Display for Vision {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Vision::Normal => write!(f, "Standard colors"),
+            Vision::Deuteranopia => write!(f, "Deuteranopia"),
+            Vision::Protanopia => write!(f, "Protanopia"),
+            Vision::Tritanopia => write!(f, "Tritanopia"),
+        }
+    }
+}
+
+const DAY: u64 = 24 * 60 * 60;
+
+pub const AGE_BUCKETS: [(u64, &str); 7] = [
+    (7 * DAY, "< 1 week"),
+    (30 * DAY, "< 1 month"),
+    (182 * DAY, "< 6 months"),
+    (365 * DAY, "< 1 year"),
+    (2 * 365 * DAY, "< 2 years"),
+    (5 * 365 * DAY, "< 5 years"),
+    (u64::MAX, "older"),
+];
+
+// heat maps from hot (recently modified) to cold (untouched for years)
+// https://colorbrewer2.org/#type=diverging&scheme=RdBu&n=7
+const AGE_COLORS: [RGBA; 7] = [
+    hex_color(0xb2182b),
+    hex_color(0xef8a62),
+    hex_color(0xfddbc7),
+    hex_color(0xf7f7f7),
+    hex_color(0xd1e5f0),
+    hex_color(0x67a9cf),
+    hex_color(0x2166ac),
+];
+// https://colorbrewer2.org/#type=diverging&scheme=PuOr&n=7
+const AGE_COLORS_RED_GREEN_SAFE: [RGBA; 7] = [
+    hex_color(0xb35806),
+    hex_color(0xf1a340),
+    hex_color(0xfee0b6),
+    hex_color(0xf7f7f7),
+    hex_color(0xd8daeb),
+    hex_color(0x998ec3),
+    hex_color(0x542788),
+];
+// https://colorbrewer2.org/#type=diverging&scheme=RdGy&n=7
+const AGE_COLORS_BLUE_YELLOW_SAFE: [RGBA; 7] = [
+    hex_color(0xb2182b),
+    hex_color(0xef8a62),
+    hex_color(0xfddbc7),
+    hex_color(0xffffff),
+    hex_color(0xe0e0e0),
+    hex_color(0x999999),
+    hex_color(0x4d4d4d),
+];
+const UNKNOWN: RGBA = hex_color(0xbdbdbd);
 
-pub fn depth_dir_color(depth: usize) -> RGBA {
-    DIR[depth % 5]
+fn age_color(mtime: Option<u64>, now: u64, colors: &[RGBA; 7]) -> RGBA {
+    match mtime {
+        None => UNKNOWN,
+        Some(mtime) => {
+            let age = now.saturating_sub(mtime);
+            AGE_BUCKETS
+                .iter()
+                .position(|(max_age, _)| age < *max_age)
+                .map(|i| colors[i])
+                .unwrap_or(UNKNOWN)
+        }
+    }
+}
+
+// https://colorbrewer2.org/#type=qualitative&scheme=Set3&n=12
+const OWNER_COLORS: [RGBA; 12] = [
+    hex_color(0x8dd3c7),
+    hex_color(0xffffb3),
+    hex_color(0xbebada),
+    hex_color(0xfb8072),
+    hex_color(0x80b1d3),
+    hex_color(0xfdb462),
+    hex_color(0xb3de69),
+    hex_color(0xfccde5),
+    hex_color(0xd9d9d9),
+    hex_color(0xbc80bd),
+    hex_color(0xccebc5),
+    hex_color(0xffed6f),
+];
+// Okabe & Ito, https://jfly.uni-koeln.de/color/
+const OWNER_COLORS_RED_GREEN_SAFE: [RGBA; 8] = [
+    hex_color(0xe69f00),
+    hex_color(0x56b4e9),
+    hex_color(0x009e73),
+    hex_color(0xf0e442),
+    hex_color(0x0072b2),
+    hex_color(0xd55e00),
+    hex_color(0xcc79a7),
+    hex_color(0x999999),
+];
+// Paul Tol's vibrant scheme, https://personal.sron.nl/~pault/
+const OWNER_COLORS_BLUE_YELLOW_SAFE: [RGBA; 7] = [
+    hex_color(0xee7733),
+    hex_color(0x0077bb),
+    hex_color(0x33bbee),
+    hex_color(0xee3377),
+    hex_color(0xcc3311),
+    hex_color(0x009988),
+    hex_color(0xbbbbbb),
+];
+
+pub fn owner_color(uid: Option<u32>, vision: Vision) -> RGBA {
+    let colors = vision.owner_colors();
+    match uid {
+        None => UNKNOWN,
+        // uids are usually allocated sequentially, so consecutive users get distinct colors
+        Some(uid) => colors[uid as usize % colors.len()],
+    }
+}
+
+// Okabe & Ito again, these stay apart for all kinds of color vision
+const GIT_COLORS: [(GitStatus, RGBA); 3] = [
+    (GitStatus::Tracked, hex_color(0x009e73)),
+    (GitStatus::Untracked, hex_color(0xe69f00)),
+    (GitStatus::Ignored, hex_color(0x56b4e9)),
+];
+
+fn git_color(status: Option<GitStatus>) -> RGBA {
+    GIT_COLORS
+        .iter()
+        .find(|(s, _)| Some(*s) == status)
+        .map(|(_, color)| *color)
+        .unwrap_or(UNKNOWN)
+}
+
+// the theme's text is made for its depth colors, the other modes have fixed palettes
+// that can be as light as the dark theme's text, those get black or white instead
+// https://www.w3.org/TR/WCAG21/#dfn-contrast-ratio
+pub fn label_color(fill: RGBA, text: RGBA) -> RGBA {
+    let linear = |c: f32| {
+        if c <= 0.04045 {
+            c / 12.92
+        } else {
+            ((c + 0.055) / 1.055).powf(2.4)
+        }
+    };
+    let luminance = |c: RGBA| 0.2126 * linear(c.r) + 0.7152 * linear(c.g) + 0.0722 * linear(c.b);
+    let contrast = |a: f32, b: f32| (a.max(b) + 0.05) / (a.min(b) + 0.05);
+    let fill_luminance = luminance(fill);
+    if contrast(fill_luminance, luminance(text)) >= 3.0 {
+        text
+    } else if contrast(fill_luminance, 0.0) >= contrast(fill_luminance, 1.0) {
+        hex_color(0x000000)
+    } else {
+        hex_color(0xffffff)
+    }
+}
+
+fn scale(ratio: f32, color: RGBA) -> RGBA {
+    RGBA {
+        r: color.r * ratio,
+        g: color.g * ratio,
+        b: color.b * ratio,
+        a: color.a,
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ColorMode {
+    #[default]
+    Depth,
+    Age,
+    Owner,
+    Git,
+}
+
+impl ColorMode {
+    pub const ALL: [ColorMode; 4] = [
+        ColorMode::Depth,
+        ColorMode::Age,
+        ColorMode::Owner,
+        ColorMode::Git,
+    ];
+}
+
+impl fmt::Display for ColorMode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ColorMode::Depth => write!(f, "Depth"),
+            ColorMode::Age => write!(f, "Age"),
+            ColorMode::Owner => write!(f, "Owner"),
+            ColorMode::Git => write!(f, "Git status"),
+        }
+    }
 }
 
-pub fn depth_file_color(depth: usize) -> RGBA {
-    FILE[depth % 5]
+pub fn now_secs() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+// picks node colors for the treemap according to the selected mode
+pub struct Colorizer {
+    mode: ColorMode,
+    vision: Vision,
+    dir: [RGBA; 5],
+    file: [RGBA; 5],
+    now: u64,
+}
+
+impl Colorizer {
+    pub fn new(mode: ColorMode, colors: &ThemeColors, vision: Vision) -> Self {
+        let (dir_base, file_base) = match vision.depth_hues() {
+            Some((dir_hue, file_hue)) => (
+                with_hue(colors.dir_base, dir_hue),
+                with_hue(colors.file_base, file_hue),
+            ),
+            None => (colors.dir_base, colors.file_base),
+        };
+        Colorizer {
+            mode,
+            vision,
+            dir: depth_palette(dir_base, colors.depth_darkening),
+            file: depth_palette(file_base, colors.depth_darkening),
+            now: now_secs(),
+        }
+    }
+
+    pub fn color(&self, node: &Node) -> RGBA {
+        match self.mode {
+            ColorMode::Depth => match node.is_file {
+                false => self.dir[node.depth as usize % 5],
+                true => self.file[node.depth as usize % 5],
+            },
+            ColorMode::Age => {
+                // a directory is as fresh as the newest file in it
+                // darken it a little to tell the header apart from its contents
+                // (HSL darkening is undefined for the grey buckets, scale instead)
+                let color = age_color(node.newest_mtime, self.now, self.vision.age_colors());
+                if node.is_file {
+                    color
+                } else {
+                    scale(0.9, color)
+                }
+            }
+            ColorMode::Owner => {
+                let color = owner_color(node.uid, self.vision);
+                if node.is_file {
+                    color
+                } else {
+                    scale(0.9, color)
+                }
+            }
+            // a directory takes the status of most of its bytes
+            ColorMode::Git if node.is_file => git_color(node.git),
+            ColorMode::Git => scale(0.9, git_color(node.git_bytes.dominant())),
+        }
+    }
+
+    // the owner legend depends on the scan, the UI builds it from the owner summary
+    pub fn legend(&self) -> Vec<(RGBA, String)> {
+        match self.mode {
+            ColorMode::Depth | ColorMode::Owner => vec![],
+            ColorMode::Age => AGE_BUCKETS
+                .iter()
+                .zip(self.vision.age_colors())
+                .map(|((_, label), color)| (*color, label.to_string()))
+                .chain(std::iter::once((UNKNOWN, "unknown".to_string())))
+                .collect(),
+            ColorMode::Git => GIT_COLORS
+                .iter()
+                .map(|(status, color)| (*color, status.to_string()))
+                .chain(std::iter::once((
+                    UNKNOWN,
+                    "not in a repository".to_string(),
+                )))
+                .collect(),
+        }
+    }
 }
@@ -0,0 +1,61 @@
+use crate::types::{Node, NodeID, Tree};
+
+pub const DAY: u64 = 24 * 60 * 60;
+
+// files of at least min_size bytes, neither modified nor accessed in min_age seconds
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct OldQuery {
+    pub min_size: u64,
+    pub min_age: u64,
+}
+
+impl Default for OldQuery {
+    fn default() -> Self {
+        Self {
+            min_size: 100 * 1024 * 1024,
+            min_age: 180 * DAY,
+        }
+    }
+}
+
+impl OldQuery {
+    // files without any known time are left out, their age can't be told
+    pub fn matches(&self, node: &Node, now: u64) -> bool {
+        node.is_file
+            && node.size >= self.min_size
+            && last_used(node).is_some_and(|t| now.saturating_sub(t) >= self.min_age)
+    }
+}
+
+// the later of the modification and access times
+pub fn last_used(node: &Node) -> Option<u64> {
+    node.mtime.max(node.atime)
+}
+
+#[derive(Debug, Clone)]
+pub struct OldFile {
+    pub node_id: NodeID,
+    pub size: u64,
+    pub path: String,
+    pub last_used: u64,
+}
+
+// the files under root matching the query, largest first
+pub fn find_old_files(tree: &Tree, root: NodeID, query: OldQuery, now: u64) -> Vec<OldFile> {
+    let mut files = vec![];
+    let mut stack = vec![root];
+    while let Some(id) = stack.pop() {
+        let node = tree.get_elem(id);
+        stack.extend(node.children.iter());
+        if query.matches(node, now) {
+            files.push(OldFile {
+                node_id: id,
+                size: node.size,
+                path: node.path.to_string_lossy().into_owned(),
+                last_used: last_used(node).unwrap_or_default(),
+            });
+        }
+    }
+    files.sort_by_key(|f| std::cmp::Reverse(f.size));
+    files
+}
@@ -0,0 +1,71 @@
+use crate::types::{NodeID, Tree};
+use once_cell::sync::Lazy;
+use std::collections::HashMap;
+
+// uid -> user name, read from the local account database
+// we only read /etc/passwd, so network accounts (LDAP etc.) will show up as numbers
+static USER_NAMES: Lazy<HashMap<u32, String>> = Lazy::new(|| read_id_names("/etc/passwd"));
+static GROUP_NAMES: Lazy<HashMap<u32, String>> = Lazy::new(|| read_id_names("/etc/group"));
+
+fn read_id_names(path: &str) -> HashMap<u32, String> {
+    // both files are formatted as name:password:id:...
+    std::fs::read_to_string(path)
+        .unwrap_or_default()
+        .lines()
+        .filter_map(|line| {
+            let mut fields = line.split(':');
+            let name = fields.next()?;
+            let id = fields.nth(1)?.parse().ok()?;
+            Some((id, name.to_string()))
+        })
+        .collect()
+}
+
+fn id_name(names: &HashMap<u32, String>, id: Option<u32>) -> String {
+    match id {
+        None => String::from("unknown"),
+        Some(id) => names.get(&id).cloned().unwrap_or_else(|| id.to_string()),
+    }
+}
+
+pub fn user_name(uid: Option<u32>) -> String {
+    id_name(&USER_NAMES, uid)
+}
+
+pub fn group_name(gid: Option<u32>) -> String {
+    id_name(&GROUP_NAMES, gid)
+}
+
+#[derive(Debug, Clone)]
+pub struct OwnerSummary {
+    pub uid: Option<u32>,
+    pub name: String,
+    pub bytes: u64,
+    pub files: u64,
+}
+
+// bytes and file counts per owner under the given node, largest first
+pub fn owner_summary(tree: &Tree, root: NodeID) -> Vec<OwnerSummary> {
+    let mut owners: HashMap<Option<u32>, (u64, u64)> = HashMap::new();
+    let mut stack = vec![root];
+    while let Some(id) = stack.pop() {
+        let node = tree.get_elem(id);
+        if node.is_file {
+            let entry = owners.entry(node.uid).or_default();
+            entry.0 += node.size;
+            entry.1 += 1;
+        }
+        stack.extend(node.children.iter());
+    }
+    let mut summary: Vec<OwnerSummary> = owners
+        .into_iter()
+        .map(|(uid, (bytes, files))| OwnerSummary {
+            uid,
+            name: user_name(uid),
+            bytes,
+            files,
+        })
+        .collect();
+    summary.sort_by_key(|owner| std::cmp::Reverse(owner.bytes));
+    summary
+}
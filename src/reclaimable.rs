@@ -0,0 +1,136 @@
+use crate::types::{Node, NodeID, Tree};
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+
+static HOME_DIR: Lazy<Option<PathBuf>> = Lazy::new(dirs::home_dir);
+
+// a kind of directory that can be deleted and made again, like build outputs and caches
+// read from the settings file, so that more can be added there
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ReclaimRule {
+    pub name: String,
+    pub dir_name: String,
+    // the directory only matches next to one of these, such as target/ next to Cargo.toml
+    #[serde(default)]
+    pub markers: Vec<String>,
+    // and only inside a directory of this name
+    #[serde(default)]
+    pub parent_name: Option<String>,
+    // and only right in the home directory, like ~/.cache
+    #[serde(default)]
+    pub in_home: bool,
+}
+
+impl ReclaimRule {
+    fn new(name: &str, dir_name: &str, markers: &[&str], parent_name: Option<&str>) -> Self {
+        Self {
+            name: name.into(),
+            dir_name: dir_name.into(),
+            markers: markers.iter().map(|m| m.to_string()).collect(),
+            parent_name: parent_name.map(String::from),
+            in_home: false,
+        }
+    }
+
+    fn in_home(self) -> Self {
+        Self {
+            in_home: true,
+            ..self
+        }
+    }
+
+    fn matches(&self, tree: &Tree, node: &Node) -> bool {
+        if node.is_file || node.name != self.dir_name {
+            return false;
+        }
+        let Some(parent) = node.parent.map(|p| tree.get_elem(p)) else {
+            return false;
+        };
+        if self
+            .parent_name
+            .as_ref()
+            .is_some_and(|name| *name != parent.name)
+        {
+            return false;
+        }
+        if self.in_home && HOME_DIR.as_ref() != Some(&parent.path) {
+            return false;
+        }
+        self.markers.is_empty()
+            || parent
+                .children
+                .iter()
+                .any(|c| self.markers.contains(&tree.get_elem(*c).name))
+    }
+}
+
+pub fn default_rules() -> Vec<ReclaimRule> {
+    vec![
+        ReclaimRule::new("Rust build output", "target", &["Cargo.toml"], None),
+        ReclaimRule::new("Node.js modules", "node_modules", &["package.json"], None),
+        ReclaimRule::new("Python virtual environment", ".venv", &[], None),
+        ReclaimRule::new("Python bytecode", "__pycache__", &[], None),
+        ReclaimRule::new("Gradle cache", ".gradle", &[], None),
+        ReclaimRule::new(
+            "Gradle build output",
+            "build",
+            &["build.gradle", "build.gradle.kts"],
+            None,
+        ),
+        ReclaimRule::new("User cache", ".cache", &[], None).in_home(),
+        ReclaimRule::new("Docker build cache", "buildkit", &[], Some("docker")),
+    ]
+}
+
+#[derive(Debug, Clone)]
+pub struct ReclaimableDir {
+    pub node_id: NodeID,
+    pub path: String,
+    pub size: u64,
+}
+
+// the matching directories of a rule
+#[derive(Debug, Clone)]
+pub struct RuleMatches {
+    pub name: String,
+    pub dirs: Vec<ReclaimableDir>,
+}
+
+impl RuleMatches {
+    pub fn size(&self) -> u64 {
+        self.dirs.iter().map(|d| d.size).sum()
+    }
+}
+
+// the directories under root matching a rule, the first matching rule wins
+// nothing inside a matching directory is looked at, it all goes with it
+// rules without matches are left out, the largest total comes first
+pub fn find_reclaimable(tree: &Tree, root: NodeID, rules: &[ReclaimRule]) -> Vec<RuleMatches> {
+    let mut matches: Vec<RuleMatches> = rules
+        .iter()
+        .map(|rule| RuleMatches {
+            name: rule.name.clone(),
+            dirs: vec![],
+        })
+        .collect();
+    // the shown root itself isn't offered, even when it matches
+    let mut stack = tree.get_elem(root).children.clone();
+    while let Some(id) = stack.pop() {
+        let node = tree.get_elem(id);
+        match rules.iter().position(|rule| rule.matches(tree, node)) {
+            Some(i) => matches[i].dirs.push(ReclaimableDir {
+                node_id: id,
+                path: node.path.to_string_lossy().into_owned(),
+                size: node.size,
+            }),
+            None => stack.extend(node.children.iter()),
+        }
+    }
+    matches.retain(|m| !m.dirs.is_empty());
+    for m in matches.iter_mut() {
+        m.dirs.sort_by_key(|d| std::cmp::Reverse(d.size));
+    }
+    matches.sort_by_key(|m| std::cmp::Reverse(m.size()));
+    matches
+}
@@ -1,5 +1,5 @@
 use crate::{
-    config,
+    config, git,
     types::{Node, Tree},
 };
 use jwalk::WalkDirGeneric;
@@ -9,7 +9,18 @@ use std::sync::{
     atomic::{AtomicBool, Ordering},
 };
 use std::thread;
-use std::time::Instant;
+use std::time::{Instant, SystemTime, UNIX_EPOCH};
+
+// what the scan leaves out, from the settings
+#[derive(Debug, Clone, Default)]
+pub struct ScanOptions {
+    // glob patterns matched against names, matching files and directories are skipped
+    pub excludes: Vec<String>,
+    // go into directories on other file systems, such as mounted drives
+    pub cross_device: bool,
+    // read the git repositories found once the scan is complete, the reports don't need them
+    pub git: bool,
+}
 
 #[derive(Debug)]
 pub struct Scan {
@@ -22,7 +33,7 @@ pub struct Scan {
 }
 
 impl Scan {
-    pub fn new(directory: &str) -> Self {
+    pub fn new(directory: &str, options: ScanOptions) -> Self {
         let path = directory.to_string();
         let tree_mutex = Arc::new(Mutex::new(Tree::new(directory)));
         let tree_mutex_clone = tree_mutex.clone();
@@ -39,6 +50,7 @@ impl Scan {
                 update_signal_clone,
                 terminate_signal_clone,
                 complete_clone,
+                options,
             )
         });
         Scan {
@@ -89,6 +101,51 @@ fn is_same_device(metadata: &std::fs::Metadata, root_device: &mut Option<u64>) -
     true
 }
 
+#[cfg(unix)]
+fn owner_ids(metadata: &std::fs::Metadata) -> (Option<u32>, Option<u32>) {
+    use std::os::unix::prelude::MetadataExt;
+    (Some(metadata.uid()), Some(metadata.gid()))
+}
+
+#[cfg(not(unix))]
+fn owner_ids(_metadata: &std::fs::Metadata) -> (Option<u32>, Option<u32>) {
+    (None, None)
+}
+
+// the space taken on disk, the permission bits and the device
+#[cfg(unix)]
+fn disk_details(metadata: &std::fs::Metadata) -> (u64, Option<u32>, Option<u64>) {
+    use std::os::unix::prelude::MetadataExt;
+    (
+        metadata.blocks() * 512,
+        Some(metadata.mode()),
+        Some(metadata.dev()),
+    )
+}
+
+#[cfg(not(unix))]
+fn disk_details(metadata: &std::fs::Metadata) -> (u64, Option<u32>, Option<u64>) {
+    (metadata.len(), None, None)
+}
+
+fn is_excluded(name: &std::ffi::OsStr, excludes: &[String]) -> bool {
+    let name = name.to_string_lossy();
+    excludes
+        .iter()
+        .any(|pattern| git::glob_match(pattern.as_bytes(), name.as_bytes()))
+}
+
+#[cfg(unix)]
+fn inode(metadata: &std::fs::Metadata) -> Option<u64> {
+    use std::os::unix::prelude::MetadataExt;
+    Some(metadata.ino())
+}
+
+#[cfg(not(unix))]
+fn inode(_metadata: &std::fs::Metadata) -> Option<u64> {
+    None
+}
+
 fn preliminary_progress_count(directory: &str) -> usize {
     let contained = fs::read_dir(directory).expect("Cannot open directory");
     contained.count()
@@ -113,11 +170,16 @@ fn preliminary_progress_count(directory: &str) -> usize {
     }
 }*/
 
+fn epoch_secs(time: std::io::Result<SystemTime>) -> Option<u64> {
+    time.ok()
+        .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
+        .map(|d| d.as_secs())
+}
+
+// None for an entry that couldn't be read or was excluded, the directory it's in is then
+// marked incomplete instead
 struct StagedEntry {
-    file_name: String,
-    path: std::path::PathBuf,
-    is_file: bool,
-    file_size: u64,
+    node: Option<Node>,
     depth: usize,
 }
 
@@ -126,36 +188,45 @@ fn walk_into_tree(
     update_signal: Arc<AtomicBool>,
     terminate_signal: Arc<AtomicBool>,
     complete: Arc<AtomicBool>,
+    options: ScanOptions,
 ) {
     let root_name = { tree.lock().unwrap().get_elem(0).name.clone() };
+    let git = options.git;
     // the root device is uninitialized, it only gets initialized when found None
     // in the is_same_device function
     let mut root_device = None;
-    let walkdir =
-        WalkDirGeneric::<(Node, Option<Result<std::fs::Metadata, jwalk::Error>>)>::new(root_name)
-            .follow_links(false)
-            .skip_hidden(false)
-            .process_read_dir(move |_depth, _path, _read_dir_state, children| {
-                children.iter_mut().for_each(move |dir_entry_result| {
-                    if let Ok(dir_entry) = dir_entry_result {
-                        if dir_entry.file_type.is_dir() {
-                            let same_device = dir_entry
-                                .metadata()
-                                .as_ref()
-                                .map(|m| is_same_device(m, &mut root_device))
-                                .unwrap_or(true);
-                            if !same_device {
-                                dir_entry.read_children_path = None;
-                            }
+    // the state of an entry is whether the excludes left it out, it's still passed on so that
+    // the directory it's in can be told apart from an empty one
+    let walkdir = WalkDirGeneric::<(Node, bool)>::new(root_name)
+        .follow_links(false)
+        .skip_hidden(false)
+        .process_read_dir(move |_depth, _path, _read_dir_state, children| {
+            let cross_device = options.cross_device;
+            let excludes = &options.excludes;
+            children.iter_mut().for_each(move |dir_entry_result| {
+                if let Ok(dir_entry) = dir_entry_result {
+                    if is_excluded(&dir_entry.file_name, excludes) {
+                        dir_entry.client_state = true;
+                        dir_entry.read_children_path = None;
+                    } else if !cross_device && dir_entry.file_type.is_dir() {
+                        let same_device = dir_entry
+                            .metadata()
+                            .as_ref()
+                            .map(|m| is_same_device(m, &mut root_device))
+                            .unwrap_or(true);
+                        if !same_device {
+                            dir_entry.read_children_path = None;
                         }
                     }
-                })
-            });
+                }
+            })
+        });
     let mut last_depth = 0;
     let mut last_node = 0;
     let mut iter = walkdir.into_iter().peekable();
     let mut staging_buffer = Vec::with_capacity(1000);
     let mut last_update = Instant::now();
+    let update_period = config::tuning().update_period();
 
     while let Some(entry) = iter.next() {
         if terminate_signal.load(Ordering::SeqCst) {
@@ -163,42 +234,102 @@ fn walk_into_tree(
         }
 
         match entry {
+            Ok(e) if e.client_state => {
+                staging_buffer.push(StagedEntry {
+                    node: None,
+                    depth: e.depth,
+                });
+            }
             Ok(e) => {
-                let file_size = e.metadata().map(|m| m.len()).unwrap_or(0);
-                let file_name = e.file_name.clone().into_string().unwrap_or_default();
-                let path = e.path();
-                let is_file = e.file_type.is_file();
+                let metadata = e.metadata().ok();
+                let (uid, gid) = metadata.as_ref().map(owner_ids).unwrap_or_default();
+                let (disk_size, mode, dev) =
+                    metadata.as_ref().map(disk_details).unwrap_or_default();
+                let node = Node {
+                    name: e.file_name.clone().into_string().unwrap_or_default(),
+                    path: e.path(),
+                    is_file: e.file_type.is_file(),
+                    is_symlink: e.file_type.is_symlink(),
+                    is_special: !(e.file_type.is_file()
+                        || e.file_type.is_dir()
+                        || e.file_type.is_symlink()),
+                    size: metadata.as_ref().map(|m| m.len()).unwrap_or(0),
+                    disk_size,
+                    mode,
+                    dev,
+                    ino: metadata.as_ref().and_then(inode),
+                    mtime: metadata.as_ref().and_then(|m| epoch_secs(m.modified())),
+                    atime: metadata.as_ref().and_then(|m| epoch_secs(m.accessed())),
+                    uid,
+                    gid,
+                    incomplete: e.read_children_error.is_some(),
+                    ..Default::default()
+                };
+                if let Some(error) = &e.read_children_error {
+                    eprintln!("Can't read: {}", error);
+                }
                 staging_buffer.push(StagedEntry {
-                    file_name,
-                    path,
-                    is_file,
-                    file_size,
+                    node: Some(node),
                     depth: e.depth,
                 });
             }
             Err(e) => {
-                println!("Can't read: {}", e);
+                eprintln!("Can't read: {}", e);
+                staging_buffer.push(StagedEntry {
+                    node: None,
+                    depth: e.depth(),
+                });
             }
         }
 
         // with time based updates, we might end up missing small folders or the last few elements
         // check for iter.peek().is_none() or just run this at the end of the loop again
-        if last_update.elapsed() >= config::UPDATE_PERIOD || iter.peek().is_none() {
+        if last_update.elapsed() >= update_period || iter.peek().is_none() {
             // acquire lock here
             let mut tree = tree.lock().unwrap();
             for e in staging_buffer.drain(..) {
+                let Some(node) = e.node else {
+                    // the directory the entry would have gone in
+                    let mut dir = last_node;
+                    for _ in e.depth..=last_depth {
+                        dir = tree.get_elem(dir).parent.unwrap_or(dir);
+                    }
+                    tree.mark_incomplete(dir);
+                    continue;
+                };
+                let incomplete = node.incomplete;
                 if e.depth > last_depth {
-                    tree.add_elem(last_node, e.file_name, e.path, e.is_file, e.file_size);
+                    tree.add_elem(last_node, node);
                 } else if e.depth == last_depth {
                     if let Some(parent) = tree.get_elem(last_node).parent {
-                        tree.add_elem(parent, e.file_name, e.path, e.is_file, e.file_size);
+                        tree.add_elem(parent, node);
+                    } else if e.depth == 0 {
+                        // the scanned directory itself
+                        tree.set_root_details(node);
                     }
                 } else {
                     let mut parent = last_node;
                     for _ in e.depth..=last_depth {
                         parent = tree.get_elem(parent).parent.unwrap_or(parent);
                     }
-                    tree.add_elem(parent, e.file_name, e.path, e.is_file, e.file_size);
+                    tree.add_elem(parent, node);
+                }
+                // the depth 0 entry only fills in the root
+                if e.depth > 0 {
+                    if incomplete {
+                        let id = tree.last_id;
+                        tree.mark_incomplete(id);
+                    }
+                    let added = tree.get_elem(tree.last_id);
+                    let (id, size, is_file, is_git) =
+                        (added.id, added.size, added.is_file, added.name == ".git");
+                    let parent = added.parent;
+                    if is_file {
+                        tree.largest_files.offer(size, id);
+                    }
+                    if is_git {
+                        tree.git_repos.push(parent.unwrap_or(0));
+                    }
                 }
                 last_depth = e.depth;
                 last_node = tree.last_id;
@@ -207,6 +338,9 @@ fn walk_into_tree(
             update_signal.store(true, Ordering::SeqCst);
         }
     }
+    if git && !terminate_signal.load(Ordering::SeqCst) {
+        git::classify_repos(&tree);
+    }
     update_signal.store(true, Ordering::SeqCst);
     complete.store(true, Ordering::SeqCst);
 
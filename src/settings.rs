@@ -0,0 +1,235 @@
+use crate::config::Tuning;
+use crate::node_color::Vision;
+use crate::reclaimable::{ReclaimRule, default_rules};
+use crate::scan::ScanOptions;
+use crate::sizes::SizeMode;
+use crate::theme::AppTheme;
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use std::{fs, io};
+
+// user choices persisted between runs
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Settings {
+    pub theme: AppTheme,
+    pub vision: Vision,
+    // outlines telling files, directories and the hovered node apart without relying on hue
+    pub cues: bool,
+    // shade the treemap so that nesting shows as 3D cushions
+    pub cushions: bool,
+    // what the reclaimable panel looks for, saved so that rules can be added to the file
+    pub reclaim_rules: Vec<ReclaimRule>,
+    pub size_mode: SizeMode,
+    // glob patterns of file and directory names the scan skips, like "node_modules" or "*.iso"
+    pub excludes: Vec<String>,
+    // whether the scan goes into other file systems mounted below the scanned directory
+    pub cross_device: bool,
+    pub tuning: Tuning,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            theme: AppTheme::default(),
+            vision: Vision::default(),
+            cues: false,
+            cushions: false,
+            reclaim_rules: default_rules(),
+            size_mode: SizeMode::default(),
+            excludes: vec![],
+            cross_device: false,
+            tuning: Tuning::default(),
+        }
+    }
+}
+
+pub fn settings_path() -> Option<PathBuf> {
+    dirs::config_dir().map(|dir| dir.join("spaceman").join("settings.toml"))
+}
+
+// takes the values of the file over one at a time, a value that doesn't fit is reported and
+// its default stays, so one bad value doesn't throw the others away
+fn from_table(file: toml::Table) -> (Settings, Vec<String>) {
+    let mut merged = match toml::Table::try_from(Settings::default()) {
+        Ok(table) => table,
+        Err(e) => return (Settings::default(), vec![e.to_string()]),
+    };
+    let mut errors = vec![];
+    merge_values(&mut merged, &[], file, &mut errors);
+    match toml::Value::Table(merged).try_into() {
+        Ok(settings) => (settings, errors),
+        Err(e) => {
+            errors.push(format!("the file is ignored, {}", e.message()));
+            (Settings::default(), errors)
+        }
+    }
+}
+
+// the table at path in the merged values, path only goes through tables
+fn table_at<'a>(table: &'a mut toml::Table, path: &[String]) -> &'a mut toml::Table {
+    path.iter()
+        .fold(table, |table, key| match table.get_mut(key) {
+            Some(toml::Value::Table(inner)) => inner,
+            _ => unreachable!("only known tables are walked into"),
+        })
+}
+
+fn merge_values(
+    merged: &mut toml::Table,
+    path: &[String],
+    file: toml::Table,
+    errors: &mut Vec<String>,
+) {
+    for (key, value) in file {
+        let name = path
+            .iter()
+            .chain([&key])
+            .cloned()
+            .collect::<Vec<_>>()
+            .join(".");
+        match (value, table_at(merged, path).get(&key)) {
+            (toml::Value::Table(inner), Some(toml::Value::Table(_))) => {
+                let mut inner_path = path.to_vec();
+                inner_path.push(key);
+                merge_values(merged, &inner_path, inner, errors);
+            }
+            (_, None) => errors.push(format!("unknown setting {} is ignored", name)),
+            (value, Some(_)) => {
+                let mut candidate = merged.clone();
+                table_at(&mut candidate, path).insert(key, value);
+                match toml::Value::Table(candidate.clone()).try_into::<Settings>() {
+                    Ok(_) => *merged = candidate,
+                    Err(e) => errors.push(format!("{} is ignored, {}", name, e.message())),
+                }
+            }
+        }
+    }
+}
+
+impl Settings {
+    // a missing or unreadable file falls back to the defaults, and invalid values to theirs
+    // what was wrong with the file is returned, and printed
+    // the file isn't written over while it has errors, see backup
+    pub fn load() -> (Self, Vec<String>) {
+        let Some(path) = settings_path() else {
+            return (Settings::default(), vec![]);
+        };
+        let (mut settings, mut errors) = match fs::read_to_string(&path) {
+            Ok(contents) => match contents.parse::<toml::Table>() {
+                Ok(file) => from_table(file),
+                Err(e) => (
+                    Settings::default(),
+                    vec![format!("the file is ignored, {}", e)],
+                ),
+            },
+            Err(_) => (Settings::default(), vec![]),
+        };
+        errors.extend(settings.validate());
+        for error in errors.iter() {
+            eprintln!("Settings file {}: {}", path.display(), error);
+        }
+        (settings, errors)
+    }
+
+    pub fn validate(&mut self) -> Vec<String> {
+        let mut errors = self.tuning.validate();
+        if self.excludes.iter().any(|e| e.trim().is_empty()) {
+            errors.push(String::from("empty excludes are left out"));
+            self.excludes.retain(|e| !e.trim().is_empty());
+        }
+        errors
+    }
+
+    pub fn scan_options(&self) -> ScanOptions {
+        ScanOptions {
+            excludes: self.excludes.clone(),
+            cross_device: self.cross_device,
+            git: true,
+        }
+    }
+
+    // copies the file next to itself, done before saving over a file that had errors
+    pub fn backup() -> io::Result<PathBuf> {
+        let path = settings_path()
+            .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "no config directory"))?;
+        let backup = path.with_extension("toml.bak");
+        fs::copy(&path, &backup)?;
+        Ok(backup)
+    }
+
+    pub fn save(&self) -> io::Result<()> {
+        let path = settings_path()
+            .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "no config directory"))?;
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        let contents = toml::to_string(self).map_err(io::Error::other)?;
+        fs::write(path, contents)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn partly_invalid_file() {
+        let file: toml::Table = r#"
+            cues = true
+            cushions = "yes"
+            size_mode = "count"
+            colour = "red"
+            excludes = ["node_modules", 5]
+
+            [tuning]
+            text_size = 20.0
+            border = "wide"
+            speed = 3
+        "#
+        .parse()
+        .unwrap();
+        let (settings, errors) = from_table(file);
+
+        // the good values are taken over
+        assert!(settings.cues);
+        assert_eq!(settings.size_mode, SizeMode::Count);
+        assert_eq!(settings.tuning.text_size, 20.0);
+        // the others keep their defaults
+        let defaults = Settings::default();
+        assert_eq!(settings.cushions, defaults.cushions);
+        assert_eq!(settings.excludes, defaults.excludes);
+        assert_eq!(settings.tuning.border, defaults.tuning.border);
+
+        let mut errors = errors;
+        errors.sort();
+        let names: Vec<&str> = errors
+            .iter()
+            .map(|e| e.split(" is ignored").next().unwrap())
+            .collect();
+        assert_eq!(
+            names,
+            vec![
+                "cushions",
+                "excludes",
+                "tuning.border",
+                "unknown setting colour",
+                "unknown setting tuning.speed",
+            ]
+        );
+    }
+
+    #[test]
+    fn empty_file_is_the_defaults() {
+        let (settings, errors) = from_table(toml::Table::new());
+        assert!(errors.is_empty());
+        assert_eq!(
+            settings.tuning.text_size,
+            Settings::default().tuning.text_size
+        );
+        assert_eq!(
+            settings.reclaim_rules.len(),
+            Settings::default().reclaim_rules.len()
+        );
+    }
+}
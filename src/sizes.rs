@@ -0,0 +1,136 @@
+use crate::types::{Node, Tree};
+use crate::utils::bytes_display;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+// what the area of a node stands for
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum SizeMode {
+    #[default]
+    Bytes,
+    Count,
+}
+
+impl SizeMode {
+    pub const ALL: [SizeMode; 2] = [SizeMode::Bytes, SizeMode::Count];
+
+    // directories count as an item themselves, so that empty ones still show up
+    pub fn of(&self, node: &Node) -> u64 {
+        match self {
+            SizeMode::Bytes => node.size,
+            SizeMode::Count if node.is_file => 1,
+            SizeMode::Count => 1 + node.file_count + node.dir_count,
+        }
+    }
+
+    pub fn display(&self, value: u64) -> String {
+        match self {
+            SizeMode::Bytes => bytes_display(value),
+            SizeMode::Count => format!("{} items", value),
+        }
+    }
+}
+
+impl fmt::Display for SizeMode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SizeMode::Bytes => write!(f, "Area by size"),
+            SizeMode::Count => write!(f, "Area by item count"),
+        }
+    }
+}
+
+// the measure the layouts size nodes by
+// values, when given, replace the measure of the node itself and are indexed by NodeID
+// this is used to show a filtered subset of the tree, nodes with 0 are left out
+pub struct Sizes {
+    pub mode: SizeMode,
+    pub values: Option<Vec<u64>>,
+}
+
+impl Sizes {
+    pub fn of(&self, node: &Node) -> u64 {
+        match &self.values {
+            Some(values) => values[node.id],
+            None => self.mode.of(node),
+        }
+    }
+
+    pub fn is_shown(&self, node: &Node) -> bool {
+        self.values.is_none() || self.of(node) > 0
+    }
+
+    pub fn label(&self, node: &Node) -> String {
+        format!("{} ({})", node.name, self.mode.display(self.of(node)))
+    }
+}
+
+// sizes of every node counting only the files kept, such as those of one owner
+// used in place of the measure of SizeMode when the treemap is filtered
+pub fn filtered_sizes(tree: &Tree, mode: SizeMode, keep: impl Fn(&Node) -> bool) -> Vec<u64> {
+    let mut sizes = vec![0; tree.elems.len()];
+    // walk from the root so that trashed nodes are left out
+    let mut stack = vec![0];
+    while let Some(id) = stack.pop() {
+        let node = tree.get_elem(id);
+        stack.extend(node.children.iter());
+        if node.is_file && keep(node) {
+            let size = mode.of(node);
+            sizes[node.id] += size;
+            let mut parent = node.parent;
+            while let Some(p) = parent {
+                sizes[p] += size;
+                parent = tree.get_elem(p).parent;
+            }
+        }
+    }
+    sizes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn node(name: &str, size: u64, is_file: bool) -> Node {
+        Node {
+            name: name.into(),
+            size,
+            is_file,
+            ..Default::default()
+        }
+    }
+
+    // the root holds a directory with two files, an empty directory and a file
+    fn tree() -> Tree {
+        let mut tree = Tree::new("/");
+        tree.add_elem(0, node("dir", 0, false));
+        tree.add_elem(1, node("a", 5, true));
+        tree.add_elem(1, node("b", 7, true));
+        tree.add_elem(0, node("empty", 0, false));
+        tree.add_elem(0, node("c", 3, true));
+        tree
+    }
+
+    #[test]
+    fn count_includes_the_directory_itself() {
+        let tree = tree();
+        let count = |id| SizeMode::Count.of(tree.get_elem(id));
+        // the root, 3 files and 2 directories below it
+        assert_eq!(count(0), 6);
+        assert_eq!(count(1), 3);
+        assert_eq!(count(2), 1);
+        // so that an empty directory still gets some area
+        assert_eq!(count(4), 1);
+        assert_eq!(SizeMode::Bytes.of(tree.get_elem(0)), 15);
+    }
+
+    #[test]
+    fn filtered_counts_only_the_files_kept() {
+        let tree = tree();
+        let sizes = filtered_sizes(&tree, SizeMode::Count, |n| n.size > 4);
+        assert_eq!(sizes, vec![2, 2, 1, 1, 0, 0]);
+        let sizes = filtered_sizes(&tree, SizeMode::Bytes, |n| n.size > 4);
+        assert_eq!(sizes, vec![12, 12, 5, 7, 0, 0]);
+    }
+}
@@ -1,31 +1,110 @@
 use crate::{
-    config::{MAX_VISIBLE_FS_DEPTH, MIN_BOX_SIZE},
+    config,
+    node_color::Colorizer,
+    sizes::Sizes,
     types::{GUINode, Node, NodeID, Rectangle, Tree},
-    utils::bytes_display,
+    utils::count_display,
 };
 
+// what gets a box in the layout
+enum Item<'a> {
+    Node(&'a Node),
+    // children of a directory too small for boxes of their own, laid out as one block
+    SmallItems {
+        parent: &'a Node,
+        nodes: Vec<&'a Node>,
+        size: u64,
+    },
+}
+
+impl Item<'_> {
+    fn size(&self, sizes: &Sizes) -> u64 {
+        match self {
+            Item::Node(node) => sizes.of(node),
+            Item::SmallItems { size, .. } => *size,
+        }
+    }
+}
+
+// children that would get less than min_box_size squared of the area are grouped together,
+// a lone small child is left as it is
+fn child_items<'a>(
+    tree: &'a Tree,
+    node: &'a Node,
+    bound: &Rectangle,
+    total_size: u64,
+    sizes: &Sizes,
+) -> Vec<Item<'a>> {
+    let area = bound.width.max(0.0) * bound.height.max(0.0);
+    let min_size = if area > 0.0 {
+        let min_box_size = config::tuning().min_box_size;
+        total_size as f64 * (min_box_size * min_box_size) as f64 / area as f64
+    } else {
+        0.0
+    };
+    let (small, big): (Vec<&Node>, Vec<&Node>) = node
+        .children
+        .iter()
+        .map(|i| tree.get_elem(*i))
+        .filter(|n| sizes.is_shown(n))
+        .partition(|n| (sizes.of(n) as f64) < min_size);
+
+    let mut items: Vec<Item> = big.into_iter().map(Item::Node).collect();
+    if small.len() > 1 {
+        let size = small.iter().map(|n| sizes.of(n)).sum();
+        items.push(Item::SmallItems {
+            parent: node,
+            nodes: small,
+            size,
+        });
+    } else {
+        items.extend(small.into_iter().map(Item::Node));
+    }
+    items
+}
+
 // wrapper function
+// bound may be larger than the visible clip area when zoomed in, nodes outside of the clip
+// are left out, and nodes covering all of it don't count towards max_visible_fs_depth
 pub fn compute_gui_nodes(
     tree: &Tree,
     root_id: NodeID,
     bound: Rectangle,
+    clip: Rectangle,
     text_offset: f32,
+    colorizer: &Colorizer,
+    sizes: &Sizes,
 ) -> Vec<GUINode> {
     let root = &tree.elems[root_id];
-    compute_gui_nodes_imp(tree, vec![root], bound, 0, text_offset)
+    compute_gui_nodes_imp(
+        tree,
+        vec![Item::Node(root)],
+        bound,
+        &clip,
+        0,
+        text_offset,
+        colorizer,
+        sizes,
+    )
 }
 
-fn compute_gui_nodes_imp(
-    tree: &Tree,
-    nodes: Vec<&Node>,
+#[allow(clippy::too_many_arguments)]
+fn compute_gui_nodes_imp<'a>(
+    tree: &'a Tree,
+    nodes: Vec<Item<'a>>,
     mut bound: Rectangle,
+    clip: &Rectangle,
     dir_level: usize,
     text_offset: f32,
+    colorizer: &Colorizer,
+    sizes: &Sizes,
 ) -> Vec<GUINode> {
-    if dir_level > MAX_VISIBLE_FS_DEPTH
-        || bound.width < MIN_BOX_SIZE
-        || bound.height < MIN_BOX_SIZE
+    let tuning = config::tuning();
+    if dir_level > tuning.max_visible_fs_depth
+        || bound.width < tuning.min_box_size
+        || bound.height < tuning.min_box_size
         || nodes.is_empty()
+        || !bound.intersects(clip)
     {
         return vec![];
     }
@@ -35,16 +114,41 @@ fn compute_gui_nodes_imp(
     // we use this simple group of nodes for recursion
     // without yielding any GUINodes
     let node_group = nodes.len() > 1;
+    let covers_clip = bound.contains_rect(clip);
 
     // if this is a directory or a file node
     if !node_group {
-        let node = nodes.first().unwrap();
+        let node = match nodes.first().unwrap() {
+            Item::Node(node) => *node,
+            Item::SmallItems {
+                parent,
+                nodes,
+                size,
+            } => {
+                // a leaf, its items are listed on click instead of being laid out
+                gui_nodes.push(GUINode {
+                    rect: bound,
+                    node_id: parent.id,
+                    is_file: true,
+                    color: colorizer.color(nodes[0]),
+                    label: format!(
+                        "… {} smaller items ({})",
+                        count_display(nodes.len() as u64),
+                        sizes.mode.display(*size)
+                    ),
+                    small_items: nodes.iter().map(|n| n.id).collect(),
+                });
+                return gui_nodes;
+            }
+        };
 
         let gui_node = GUINode {
             rect: bound.clone(),
             node_id: node.id,
-            color: node.color(),
-            label: format!("{} ({})", node.name, bytes_display(node.size)),
+            is_file: node.is_file,
+            color: colorizer.color(node),
+            label: sizes.label(node),
+            small_items: vec![],
         };
 
         gui_nodes.push(gui_node);
@@ -62,58 +166,68 @@ fn compute_gui_nodes_imp(
 
     // if we have a directory or a node group, recurse
     let (children, subdir_level, total_size) = if node_group {
-        let size = nodes.iter().fold(0, |acc, n| acc + n.size);
+        let size = nodes.iter().fold(0, |acc, n| acc + n.size(sizes));
         (nodes, 0, size)
     } else {
-        let node = nodes.first().unwrap();
+        let Some(Item::Node(node)) = nodes.first() else {
+            unreachable!()
+        };
+        let size = sizes.of(node);
         (
-            node.children.iter().map(|i| tree.get_elem(*i)).collect(),
-            1,
-            node.size,
+            child_items(tree, node, &bound, size, sizes),
+            if covers_clip { 0 } else { 1 },
+            size,
         )
     };
 
     if !children.is_empty() {
-        let (group_a, bound_a, group_b, bound_b) = squarify(children, bound, total_size);
+        let (group_a, bound_a, group_b, bound_b) = squarify(children, bound, total_size, sizes);
 
         // recurse
         gui_nodes.extend(compute_gui_nodes_imp(
             tree,
             group_a,
             bound_a,
+            clip,
             dir_level + subdir_level,
             text_offset,
+            colorizer,
+            sizes,
         ));
         gui_nodes.extend(compute_gui_nodes_imp(
             tree,
             group_b,
             bound_b,
+            clip,
             dir_level + subdir_level,
             text_offset,
+            colorizer,
+            sizes,
         ));
     }
 
     gui_nodes
 }
 
-fn squarify(
-    mut nodes: Vec<&Node>,
+fn squarify<'a>(
+    mut nodes: Vec<Item<'a>>,
     bound: Rectangle,
     total_size: u64,
-) -> (Vec<&Node>, Rectangle, Vec<&Node>, Rectangle) {
+    sizes: &Sizes,
+) -> (Vec<Item<'a>>, Rectangle, Vec<Item<'a>>, Rectangle) {
     // sort by size and split into halves
 
-    nodes.sort_by_key(|x| x.size);
+    nodes.sort_by_key(|x| x.size(sizes));
 
     let mut size_a = 0;
     let mut split = 0;
     while size_a < total_size / 2 && split < nodes.len() {
-        size_a += nodes[split].size;
+        size_a += nodes[split].size(sizes);
         split += 1;
     }
     if split == nodes.len() {
         split -= 1;
-        size_a -= nodes[split].size;
+        size_a -= nodes[split].size(sizes);
     }
 
     let mut vec_a = nodes;
@@ -158,3 +272,73 @@ fn squarify(
 
     (vec_a, bound_a, vec_b, bound_b)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sizes::SizeMode;
+
+    // a directory of files with the given sizes under the root
+    fn tree_of(sizes: &[u64]) -> Tree {
+        let mut tree = Tree::new("/");
+        for (i, size) in sizes.iter().enumerate() {
+            tree.add_elem(
+                0,
+                Node {
+                    name: i.to_string(),
+                    size: *size,
+                    is_file: true,
+                    ..Default::default()
+                },
+            );
+        }
+        tree
+    }
+
+    // the names of the nodes with a box of their own, and of those grouped together
+    fn layout(sizes: &[u64]) -> (Vec<String>, Vec<String>) {
+        let tree = tree_of(sizes);
+        let root = tree.get_elem(0);
+        let bound = Rectangle {
+            x: 0.0,
+            y: 0.0,
+            width: 100.0,
+            height: 100.0,
+        };
+        let sizes = Sizes {
+            mode: SizeMode::Bytes,
+            values: None,
+        };
+        let mut own = vec![];
+        let mut grouped = vec![];
+        for item in child_items(&tree, root, &bound, root.size, &sizes) {
+            match item {
+                Item::Node(node) => own.push(node.name.clone()),
+                Item::SmallItems { nodes, size, .. } => {
+                    assert_eq!(size, nodes.iter().map(|n| n.size).sum::<u64>());
+                    grouped.extend(nodes.iter().map(|n| n.name.clone()));
+                }
+            }
+        }
+        own.sort();
+        grouped.sort();
+        (own, grouped)
+    }
+
+    #[test]
+    fn small_items_threshold() {
+        // 100 by 100 pixels with boxes of at least 20 by 20, so under 4% of the size is small
+        // and 40 of 1000 is just big enough
+        assert_eq!(config::tuning().min_box_size, config::MIN_BOX_SIZE);
+        let (own, grouped) = layout(&[900, 40, 30, 20, 10]);
+        assert_eq!(own, vec!["0", "1"]);
+        assert_eq!(grouped, vec!["2", "3", "4"]);
+    }
+
+    #[test]
+    fn lone_small_item_keeps_its_box() {
+        let (own, grouped) = layout(&[990, 10]);
+        assert_eq!(own, vec!["0", "1"]);
+        assert!(grouped.is_empty());
+    }
+}
@@ -0,0 +1,70 @@
+use crate::config;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::{fs, io};
+
+// the size and position the window was left at
+// the position isn't known everywhere, Wayland doesn't tell it
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct WindowState {
+    pub width: f32,
+    pub height: f32,
+    pub x: Option<f32>,
+    pub y: Option<f32>,
+}
+
+// remembered between runs without being chosen, unlike the settings
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AppState {
+    // the scanned directories, most recent first
+    pub recent_roots: Vec<PathBuf>,
+    pub window: Option<WindowState>,
+}
+
+// only Linux has a state directory, elsewhere it goes with the local data
+fn state_path() -> Option<PathBuf> {
+    dirs::state_dir()
+        .or_else(dirs::data_local_dir)
+        .map(|dir| dir.join("spaceman").join("state.toml"))
+}
+
+impl AppState {
+    // a missing or invalid file starts over
+    pub fn load() -> Self {
+        let Some(path) = state_path() else {
+            return AppState::default();
+        };
+        match fs::read_to_string(&path) {
+            Ok(contents) => toml::from_str(&contents).unwrap_or_else(|e| {
+                eprintln!("Ignoring invalid state file {}: {}", path.display(), e);
+                AppState::default()
+            }),
+            Err(_) => AppState::default(),
+        }
+    }
+
+    pub fn save(&self) -> io::Result<()> {
+        let path = state_path()
+            .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "no state directory"))?;
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        let contents = toml::to_string(self).map_err(io::Error::other)?;
+        fs::write(path, contents)
+    }
+
+    // moves the directory to the front, paths are made absolute so that "." is remembered right
+    // not canonicalized, that gives \\?\C:\ paths on Windows, unlike the folder picker
+    pub fn add_recent_root(&mut self, path: &Path) {
+        let path = std::path::absolute(path).unwrap_or_else(|_| path.to_path_buf());
+        self.recent_roots.retain(|p| *p != path);
+        self.recent_roots.insert(0, path);
+        self.recent_roots.truncate(config::MAX_RECENT_ROOTS);
+    }
+
+    // the recent directories that are still there
+    pub fn existing_roots(&self) -> impl Iterator<Item = &PathBuf> {
+        self.recent_roots.iter().filter(|p| p.is_dir())
+    }
+}
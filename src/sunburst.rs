@@ -0,0 +1,75 @@
+use crate::{
+    config::{MIN_ARC_ANGLE, SUNBURST_RINGS},
+    node_color::Colorizer,
+    sizes::Sizes,
+    types::{NodeID, RGBA, Tree},
+};
+use std::f32::consts::TAU;
+
+// a ring segment, angles are in radians clockwise from the top
+// ring 0 is the disc in the middle showing the root
+#[derive(Debug, Clone)]
+pub struct SunburstNode {
+    pub node_id: NodeID,
+    pub is_file: bool,
+    pub ring: usize,
+    pub start_angle: f32,
+    pub end_angle: f32,
+    pub color: RGBA,
+    pub label: String,
+}
+
+impl SunburstNode {
+    pub fn contains(&self, ring: usize, angle: f32) -> bool {
+        self.ring == ring && angle >= self.start_angle && angle < self.end_angle
+    }
+}
+
+pub fn compute_sunburst_nodes(
+    tree: &Tree,
+    root_id: NodeID,
+    colorizer: &Colorizer,
+    sizes: &Sizes,
+) -> Vec<SunburstNode> {
+    let size = |id: NodeID| sizes.of(tree.get_elem(id));
+    let mut sunburst_nodes = vec![];
+    // (node, ring, start angle, end angle)
+    let mut stack = vec![(root_id, 0, 0.0, TAU)];
+    while let Some((id, ring, start_angle, end_angle)) = stack.pop() {
+        let node = tree.get_elem(id);
+        sunburst_nodes.push(SunburstNode {
+            node_id: id,
+            is_file: node.is_file,
+            ring,
+            start_angle,
+            end_angle,
+            color: colorizer.color(node),
+            label: sizes.label(node),
+        });
+
+        let total = size(id);
+        if ring >= SUNBURST_RINGS || total == 0 {
+            continue;
+        }
+        let mut children: Vec<NodeID> = node
+            .children
+            .iter()
+            .copied()
+            .filter(|c| size(*c) > 0)
+            .collect();
+        children.sort_by_key(|c| std::cmp::Reverse(size(*c)));
+
+        let span = end_angle - start_angle;
+        let mut angle = start_angle;
+        for child in children {
+            let child_span = span * (size(child) as f32 / total as f32);
+            // the children are sorted, the rest will be even smaller
+            if child_span < MIN_ARC_ANGLE {
+                break;
+            }
+            stack.push((child, ring + 1, angle, angle + child_span));
+            angle += child_span;
+        }
+    }
+    sunburst_nodes
+}
@@ -0,0 +1,122 @@
+use crate::node_color::hex_color;
+use crate::types::RGBA;
+use iced::Theme;
+use iced::theme::Palette;
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum AppTheme {
+    #[default]
+    Light,
+    Dark,
+    HighContrast,
+}
+
+impl AppTheme {
+    pub const ALL: [AppTheme; 3] = [AppTheme::Light, AppTheme::Dark, AppTheme::HighContrast];
+
+    pub fn colors(&self) -> &'static ThemeColors {
+        match self {
+            AppTheme::Light => &LIGHT,
+            AppTheme::Dark => &DARK,
+            AppTheme::HighContrast => &HIGH_CONTRAST,
+        }
+    }
+}
+
+impl fmt::Display for AppTheme {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AppTheme::Light => write!(f, "Light"),
+            AppTheme::Dark => write!(f, "Dark"),
+            AppTheme::HighContrast => write!(f, "High contrast"),
+        }
+    }
+}
+
+// everything drawn by us rather than by iced widgets
+pub struct ThemeColors {
+    pub iced: Theme,
+    // directory and file colors, darkened by depth_darkening at each level
+    // see node_color::depth_palette
+    pub dir_base: RGBA,
+    pub file_base: RGBA,
+    pub depth_darkening: f32,
+    pub text: RGBA,
+    // shows through the gaps between the treemap rectangles
+    pub border: RGBA,
+    pub empty_background: RGBA,
+    pub menu_background: RGBA,
+    pub menu_hover: RGBA,
+    pub menu_text: RGBA,
+    pub menu_border: RGBA,
+    // the ring around the keyboard selection
+    pub focus: RGBA,
+}
+
+static LIGHT: Lazy<ThemeColors> = Lazy::new(|| ThemeColors {
+    iced: Theme::TokyoNightLight,
+    // https://www.schemecolor.com/light-to-dark-blue.php
+    // light blue #b6d4f2 and pink #f4b9d1
+    dir_base: hex_color(0xb6d4f2),
+    file_base: hex_color(0xf4b9d1),
+    depth_darkening: 0.87,
+    text: hex_color(0x000000),
+    border: hex_color(0xe1e2e7),
+    empty_background: hex_color(0xf2f2f2),
+    menu_background: hex_color(0x262626),
+    menu_hover: hex_color(0x404040),
+    menu_text: hex_color(0xffffff),
+    menu_border: hex_color(0x000000),
+    focus: hex_color(0xe8590c),
+});
+
+static DARK: Lazy<ThemeColors> = Lazy::new(|| ThemeColors {
+    iced: Theme::TokyoNight,
+    dir_base: hex_color(0x3d5a80),
+    file_base: hex_color(0x7a4a6a),
+    depth_darkening: 0.9,
+    text: hex_color(0xe6e6e6),
+    border: hex_color(0x1a1b26),
+    empty_background: hex_color(0x24283b),
+    menu_background: hex_color(0xe6e6e6),
+    menu_hover: hex_color(0xbfbfbf),
+    menu_text: hex_color(0x1a1b26),
+    menu_border: hex_color(0xffffff),
+    focus: hex_color(0xff9e64),
+});
+
+static HIGH_CONTRAST: Lazy<ThemeColors> = Lazy::new(|| ThemeColors {
+    iced: Theme::custom(
+        "High contrast",
+        Palette {
+            background: iced::Color::BLACK,
+            text: iced::Color::WHITE,
+            primary: iced::color!(0xffd600),
+            success: iced::color!(0x00e676),
+            warning: iced::color!(0xffab00),
+            danger: iced::color!(0xff1744),
+        },
+    ),
+    // bright colors that darken slowly, so black text stays readable at every depth
+    dir_base: hex_color(0x00e5ff),
+    file_base: hex_color(0xffd600),
+    depth_darkening: 0.93,
+    text: hex_color(0x000000),
+    border: hex_color(0xffffff),
+    empty_background: hex_color(0x000000),
+    menu_background: hex_color(0x000000),
+    menu_hover: hex_color(0x0050c8),
+    menu_text: hex_color(0xffffff),
+    menu_border: hex_color(0xffffff),
+    focus: hex_color(0xff00ff),
+});
+
+impl From<RGBA> for iced::Color {
+    fn from(color: RGBA) -> Self {
+        iced::Color::from_rgba(color.r, color.g, color.b, color.a)
+    }
+}
@@ -1,4 +1,7 @@
-use crate::node_color;
+use crate::config::LARGEST_FILES;
+use crate::largest::LargestFiles;
+use std::collections::HashSet;
+use std::fmt;
 use std::path::PathBuf;
 
 #[derive(Debug, Clone, Copy)]
@@ -13,6 +16,16 @@ impl Rectangle {
     pub fn contains_point(&self, px: f32, py: f32) -> bool {
         px >= self.x && px <= self.x + self.width && py >= self.y && py <= self.y + self.height
     }
+    pub fn intersects(&self, other: &Rectangle) -> bool {
+        self.x < other.x + other.width
+            && other.x < self.x + self.width
+            && self.y < other.y + other.height
+            && other.y < self.y + self.height
+    }
+    pub fn contains_rect(&self, other: &Rectangle) -> bool {
+        self.contains_point(other.x, other.y)
+            && self.contains_point(other.x + other.width, other.y + other.height)
+    }
 }
 
 #[derive(Debug, Clone, Copy)]
@@ -25,6 +38,63 @@ pub struct RGBA {
 
 pub type NodeID = usize;
 
+// where a file stands in the git repository around it
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GitStatus {
+    Tracked,
+    Ignored,
+    Untracked,
+}
+
+impl fmt::Display for GitStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            GitStatus::Tracked => write!(f, "tracked"),
+            GitStatus::Ignored => write!(f, "ignored"),
+            GitStatus::Untracked => write!(f, "untracked"),
+        }
+    }
+}
+
+// bytes of the files under a node by git status, files outside of repositories not included
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct GitBytes {
+    pub tracked: u64,
+    pub ignored: u64,
+    pub untracked: u64,
+}
+
+impl GitBytes {
+    pub fn total(&self) -> u64 {
+        self.tracked + self.ignored + self.untracked
+    }
+
+    // the status most of the bytes have
+    pub fn dominant(&self) -> Option<GitStatus> {
+        if self.total() == 0 {
+            None
+        } else if self.tracked >= self.ignored && self.tracked >= self.untracked {
+            Some(GitStatus::Tracked)
+        } else if self.ignored >= self.untracked {
+            Some(GitStatus::Ignored)
+        } else {
+            Some(GitStatus::Untracked)
+        }
+    }
+
+    fn add(&mut self, other: GitBytes, negative: bool) {
+        if negative {
+            self.tracked -= other.tracked;
+            self.ignored -= other.ignored;
+            self.untracked -= other.untracked;
+        } else {
+            self.tracked += other.tracked;
+            self.ignored += other.ignored;
+            self.untracked += other.untracked;
+        }
+    }
+}
+
 #[derive(Debug, Default, Clone)]
 pub struct Node {
     pub id: NodeID,
@@ -35,13 +105,58 @@ pub struct Node {
     pub is_file: bool,
     pub parent: Option<NodeID>,
     pub children: Vec<NodeID>,
+    // timestamps are in seconds since the unix epoch
+    pub mtime: Option<u64>,
+    pub atime: Option<u64>,
+    // newest and oldest mtime among the files under this node
+    // for a file these are equal to its own mtime
+    pub newest_mtime: Option<u64>,
+    pub oldest_mtime: Option<u64>,
+    // only available on unix
+    pub uid: Option<u32>,
+    pub gid: Option<u32>,
+    // allocated space, summed up for directories like size
+    // sparse files take less than their size, small files usually more
+    pub disk_size: u64,
+    pub is_symlink: bool,
+    // a FIFO, socket or device node, neither a regular file nor a directory
+    // counted with the files, so that a directory holding one isn't taken as empty
+    pub is_special: bool,
+    // permission bits and the device, only available on unix
+    pub mode: Option<u32>,
+    pub dev: Option<u64>,
+    // hard links to one file share the device and inode
+    pub ino: Option<u64>,
+    // everything below this node, the node itself not included
+    pub file_count: u64,
+    pub dir_count: u64,
+    // only set once the scan is complete, see git::classify_repos
+    pub git: Option<GitStatus>,
+    // summed up for directories like size
+    pub git_bytes: GitBytes,
+    // something below this directory couldn't be read or was excluded, so the counts
+    // and sizes leave it out, and the directory can't be taken as empty
+    pub incomplete: bool,
+}
+
+// what a node adds to each of its ancestors
+#[derive(Debug, Clone, Copy)]
+struct Contribution {
+    size: u64,
+    disk_size: u64,
+    files: u64,
+    dirs: u64,
+    git_bytes: GitBytes,
 }
 
-impl Node {
-    pub fn color(&self) -> RGBA {
-        match self.is_file {
-            false => node_color::depth_dir_color(self.depth as usize),
-            true => node_color::depth_file_color(self.depth as usize),
+impl Contribution {
+    fn of(node: &Node) -> Self {
+        Contribution {
+            size: node.size,
+            disk_size: node.disk_size,
+            files: node.file_count + (node.is_file || node.is_special) as u64,
+            dirs: node.dir_count + !(node.is_file || node.is_special) as u64,
+            git_bytes: node.git_bytes,
         }
     }
 }
@@ -52,6 +167,12 @@ pub struct Tree {
     // https://aloso.github.io/2021/03/09/creating-an-iterator
     pub elems: Vec<Node>,
     pub last_id: NodeID,
+    // the directories holding a .git, found while scanning
+    pub git_repos: Vec<NodeID>,
+    // filled in by the scan as it adds files, found again by refill_largest_files after a trash
+    pub largest_files: LargestFiles,
+    // the nodes taken out by invalidate_elem, whatever is below them goes too
+    pub detached: HashSet<NodeID>,
 }
 
 impl Tree {
@@ -63,43 +184,88 @@ impl Tree {
                 ..Default::default()
             }],
             last_id: 0,
+            git_repos: vec![],
+            largest_files: LargestFiles::new(LARGEST_FILES),
+            detached: HashSet::new(),
         }
     }
-    fn propagate_child_size(&mut self, mut node: NodeID, size: u64, negative: bool) {
+    fn propagate_child_size(&mut self, mut node: NodeID, added: Contribution, negative: bool) {
         while let Some(p) = self.elems[node].parent {
+            let parent = &mut self.elems[p];
             if negative {
-                self.elems[p].size -= size;
+                parent.size -= added.size;
+                parent.disk_size -= added.disk_size;
+                parent.file_count -= added.files;
+                parent.dir_count -= added.dirs;
+                parent.git_bytes.add(added.git_bytes, true);
             } else {
-                self.elems[p].size += size;
+                parent.size += added.size;
+                parent.disk_size += added.disk_size;
+                parent.file_count += added.files;
+                parent.dir_count += added.dirs;
+                parent.git_bytes.add(added.git_bytes, false);
             }
             node = p;
         }
     }
-    pub fn add_elem(
-        &mut self,
-        parent: NodeID,
-        name: String,
-        path: PathBuf,
-        is_file: bool,
-        size: u64,
-    ) {
+    fn propagate_child_mtime(&mut self, mut node: NodeID) {
+        let (newest, oldest) = (self.elems[node].newest_mtime, self.elems[node].oldest_mtime);
+        while let Some(p) = self.elems[node].parent {
+            let parent = &mut self.elems[p];
+            let new_newest = parent.newest_mtime.max(newest);
+            let new_oldest = match (parent.oldest_mtime, oldest) {
+                (Some(a), Some(b)) => Some(a.min(b)),
+                (a, b) => a.or(b),
+            };
+            if new_newest == parent.newest_mtime && new_oldest == parent.oldest_mtime {
+                break;
+            }
+            parent.newest_mtime = new_newest;
+            parent.oldest_mtime = new_oldest;
+            node = p;
+        }
+    }
+    // after a removal we can't subtract from a min/max, recompute from the remaining children
+    fn recompute_ancestor_mtimes(&mut self, node: NodeID) {
+        let mut parent = self.elems[node].parent;
+        while let Some(p) = parent {
+            let children = &self.elems[p].children;
+            let newest = children
+                .iter()
+                .filter_map(|c| self.elems[*c].newest_mtime)
+                .max();
+            let oldest = children
+                .iter()
+                .filter_map(|c| self.elems[*c].oldest_mtime)
+                .min();
+            self.elems[p].newest_mtime = newest;
+            self.elems[p].oldest_mtime = oldest;
+            parent = self.elems[p].parent;
+        }
+    }
+    // takes a node with its own data filled in, tree links are set here
+    pub fn add_elem(&mut self, parent: NodeID, mut node: Node) {
         self.last_id += 1;
-        let node = Node {
-            id: self.last_id,
-            name,
-            path,
-            size,
-            depth: self.elems[parent].depth + 1,
-            is_file,
-            parent: Some(parent),
-            children: vec![],
-        };
+        node.id = self.last_id;
+        node.depth = self.elems[parent].depth + 1;
+        node.parent = Some(parent);
+        node.children = vec![];
+        node.file_count = 0;
+        node.dir_count = 0;
+        node.git = None;
+        node.git_bytes = GitBytes::default();
+        if node.is_file {
+            node.newest_mtime = node.mtime;
+            node.oldest_mtime = node.mtime;
+        }
+        let added = Contribution::of(&node);
         self.elems[parent].children.push(self.last_id);
         self.elems.push(node);
-        self.propagate_child_size(self.last_id, size, false);
+        self.propagate_child_size(self.last_id, added, false);
+        self.propagate_child_mtime(self.last_id);
     }
     pub fn invalidate_elem(&mut self, node: NodeID) {
-        let size = self.elems[node].size;
+        let removed = Contribution::of(&self.elems[node]);
         let parent_id = self.elems[node].parent;
         if let Some(parent_id) = parent_id {
             let parent = &mut self.elems[parent_id];
@@ -107,7 +273,106 @@ impl Tree {
                 parent.children.remove(pos);
             }
         }
-        self.propagate_child_size(node, size, true);
+        self.detached.insert(node);
+        self.propagate_child_size(node, removed, true);
+        self.recompute_ancestor_mtimes(node);
+    }
+    // counts the file's bytes for the status in its ancestors too, a second call replaces them
+    pub fn set_git_status(&mut self, id: NodeID, status: GitStatus) {
+        // a status set before is taken back out of the ancestors first
+        let previous = self.elems[id].git_bytes;
+        if previous.total() > 0 {
+            let removed = Contribution {
+                size: 0,
+                disk_size: 0,
+                files: 0,
+                dirs: 0,
+                git_bytes: previous,
+            };
+            self.propagate_child_size(id, removed, true);
+        }
+        let node = &mut self.elems[id];
+        let mut bytes = GitBytes::default();
+        match status {
+            GitStatus::Tracked => bytes.tracked = node.size,
+            GitStatus::Ignored => bytes.ignored = node.size,
+            GitStatus::Untracked => bytes.untracked = node.size,
+        }
+        node.git = Some(status);
+        node.git_bytes = bytes;
+        let added = Contribution {
+            size: 0,
+            disk_size: 0,
+            files: 0,
+            dirs: 0,
+            git_bytes: bytes,
+        };
+        self.propagate_child_size(id, added, false);
+    }
+    // marks the directory and everything above it
+    pub fn mark_incomplete(&mut self, id: NodeID) {
+        self.elems[id].incomplete = true;
+        let mut parent = self.elems[id].parent;
+        while let Some(p) = parent {
+            if self.elems[p].incomplete {
+                break;
+            }
+            self.elems[p].incomplete = true;
+            parent = self.elems[p].parent;
+        }
+    }
+    // the root is made before the scan starts, this fills in what the scan found out about it
+    // sizes are left alone, they only sum up the contents
+    pub fn set_root_details(&mut self, node: Node) {
+        let root = &mut self.elems[0];
+        root.mtime = node.mtime;
+        root.atime = node.atime;
+        root.uid = node.uid;
+        root.gid = node.gid;
+        root.is_symlink = node.is_symlink;
+        root.mode = node.mode;
+        root.dev = node.dev;
+    }
+    // only what is still attached, the trashed files make room for the next largest
+    pub fn refill_largest_files(&mut self) {
+        let mut largest_files = LargestFiles::new(LARGEST_FILES);
+        let mut stack = vec![0];
+        while let Some(id) = stack.pop() {
+            let node = &self.elems[id];
+            if node.is_file {
+                largest_files.offer(node.size, id);
+            }
+            stack.extend(node.children.iter());
+        }
+        self.largest_files = largest_files;
+    }
+    // the node and its ancestors, starting from the root
+    pub fn lineage(&self, id: NodeID) -> Vec<NodeID> {
+        let mut lineage = vec![id];
+        while let Some(p) = self.elems[*lineage.last().unwrap()].parent {
+            lineage.push(p);
+        }
+        lineage.reverse();
+        lineage
+    }
+    // a directory on another device than its parent, the scan doesn't go into these
+    pub fn is_mount_point(&self, id: NodeID) -> bool {
+        let node = &self.elems[id];
+        match (node.dev, node.parent) {
+            (Some(dev), Some(p)) => !node.is_file && self.elems[p].dev.is_some_and(|d| d != dev),
+            _ => false,
+        }
+    }
+    // invalidated nodes stay in elems, but they or an ancestor are gone from the children
+    pub fn is_attached(&self, id: NodeID) -> bool {
+        let mut id = Some(id);
+        while let Some(i) = id {
+            if self.detached.contains(&i) {
+                return false;
+            }
+            id = self.elems[i].parent;
+        }
+        true
     }
     pub fn get_elem(&self, id: NodeID) -> &Node {
         &self.elems[id]
@@ -135,6 +400,42 @@ impl Default for Tree {
 pub struct GUINode {
     pub rect: Rectangle,
     pub node_id: NodeID,
+    pub is_file: bool,
     pub color: RGBA,
     pub label: String,
+    // when not empty, this is a block standing for these small children of node_id
+    // instead of node_id itself
+    pub small_items: Vec<NodeID>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn git_status_set_again_is_counted_once() {
+        let mut tree = Tree::new("/");
+        tree.add_elem(
+            0,
+            Node {
+                name: String::from("dir"),
+                ..Default::default()
+            },
+        );
+        tree.add_elem(
+            1,
+            Node {
+                name: String::from("file"),
+                size: 10,
+                is_file: true,
+                ..Default::default()
+            },
+        );
+        tree.set_git_status(2, GitStatus::Untracked);
+        tree.set_git_status(2, GitStatus::Tracked);
+        for id in [0, 1, 2] {
+            let bytes = tree.get_elem(id).git_bytes;
+            assert_eq!((bytes.tracked, bytes.untracked, bytes.total()), (10, 0, 10));
+        }
+    }
 }
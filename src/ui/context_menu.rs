@@ -1,17 +1,27 @@
 use crate::config;
+use crate::theme::ThemeColors;
 use crate::types::NodeID;
 use iced::widget::canvas::{self, Frame};
 use iced::{Color, Pixels, Point, Rectangle, Size};
 
 pub const OPTIONS: [&str; 2] = ["Show", "Trash"];
 pub const MENU_WIDTH: f32 = 140.0;
-pub const ITEM_HEIGHT: f32 = config::TEXT_SIZE + 14.0;
-pub const MENU_HEIGHT: f32 = ITEM_HEIGHT * OPTIONS.len() as f32;
+
+// the text size can be changed in the settings
+pub fn item_height() -> f32 {
+    config::tuning().text_size + 14.0
+}
+
+pub fn menu_height() -> f32 {
+    item_height() * OPTIONS.len() as f32
+}
 
 // save the position relative to our parent widget
 pub struct ContextMenu {
     pub target_node: NodeID,
     pub position: Point,
+    // picked with the arrow keys
+    pub selected: Option<usize>,
 }
 
 impl ContextMenu {
@@ -19,6 +29,7 @@ impl ContextMenu {
         Self {
             target_node,
             position,
+            selected: None,
         }
     }
 
@@ -27,57 +38,78 @@ impl ContextMenu {
             x: self.position.x,
             y: self.position.y,
             width: MENU_WIDTH,
-            height: MENU_HEIGHT,
+            height: menu_height(),
         }
     }
 
     fn get_hovered_index(&self, cursor_pos: Point) -> Option<usize> {
         if self.get_bounds().contains(cursor_pos) {
             let relative_y = cursor_pos.y - self.position.y;
-            let idx = (relative_y / ITEM_HEIGHT).floor() as usize;
+            let idx = (relative_y / item_height()).floor() as usize;
             if idx < OPTIONS.len() { Some(idx) } else { None }
         } else {
             None
         }
     }
 
+    pub fn get_selected_action(&self) -> Option<String> {
+        self.selected
+            .and_then(|idx| OPTIONS.get(idx).map(|x| (*x).to_owned()))
+    }
+
+    // moves the keyboard selection, starting at the first item
+    pub fn move_selection(&mut self, step: isize) {
+        self.selected = Some(match self.selected {
+            Some(idx) => idx.saturating_add_signed(step).min(OPTIONS.len() - 1),
+            None => 0,
+        });
+    }
+
     pub fn get_hovered_action(&self, cursor_pos: Point) -> Option<String> {
         self.get_hovered_index(cursor_pos)
             .and_then(|idx| OPTIONS.get(idx).map(|x| (*x).to_owned()))
     }
 
-    pub fn draw(&self, frame: &mut Frame, cursor_pos: Option<Point>) {
-        let menu_size = Size::new(MENU_WIDTH, MENU_HEIGHT);
-        let hovered_idx = cursor_pos.and_then(|pos| self.get_hovered_index(pos));
+    pub fn draw(&self, frame: &mut Frame, cursor_pos: Option<Point>, colors: &ThemeColors) {
+        let item_height = item_height();
+        let menu_size = Size::new(MENU_WIDTH, item_height * OPTIONS.len() as f32);
+        let hovered_idx = cursor_pos
+            .and_then(|pos| self.get_hovered_index(pos))
+            .or(self.selected);
 
         // draw menu background
-        frame.fill_rectangle(self.position, menu_size, Color::from_rgb(0.15, 0.15, 0.15));
+        frame.fill_rectangle(
+            self.position,
+            menu_size,
+            Color::from(colors.menu_background),
+        );
         frame.stroke_rectangle(
             self.position,
             menu_size,
             canvas::Stroke {
                 width: 1.0,
                 ..Default::default()
-            },
+            }
+            .with_color(Color::from(colors.menu_border)),
         );
 
         // draw options
         for (i, opt) in OPTIONS.iter().enumerate() {
-            let item_y = self.position.y + (i as f32 * ITEM_HEIGHT);
+            let item_y = self.position.y + (i as f32 * item_height);
 
             if hovered_idx == Some(i) {
                 frame.fill_rectangle(
                     Point::new(self.position.x + 1.0, item_y + 1.0),
-                    Size::new(MENU_WIDTH - 2.0, ITEM_HEIGHT - 2.0),
-                    Color::from_rgb(0.25, 0.25, 0.25),
+                    Size::new(MENU_WIDTH - 2.0, item_height - 2.0),
+                    Color::from(colors.menu_hover),
                 );
             }
 
             frame.fill_text(canvas::Text {
                 content: opt.to_string(),
                 position: Point::new(self.position.x + 4.0, item_y + 6.0),
-                color: Color::WHITE,
-                size: Pixels(config::TEXT_SIZE),
+                color: Color::from(colors.menu_text),
+                size: Pixels(config::tuning().text_size),
                 ..Default::default()
             });
         }
@@ -1,19 +1,48 @@
 mod context_menu;
+mod panel;
+mod settings_dialog;
+mod sunburst;
+mod zoom;
 
 use iced::keyboard::key;
-use iced::keyboard::key::Named::{Backspace, Escape};
+use iced::keyboard::key::Named::{
+    ArrowDown, ArrowLeft, ArrowRight, ArrowUp, Backspace, ContextMenu, Delete, Enter, Escape, F10,
+};
 use iced::mouse;
 use iced::widget::canvas::{self, Canvas, Geometry, Program};
-use iced::widget::{button, center, center_x, column, container, progress_bar, row, text, tooltip};
+use iced::widget::image;
+use iced::widget::{
+    button, center, center_x, column, container, mouse_area, pick_list, progress_bar, row,
+    scrollable, text, tooltip,
+};
 use iced::{Background, Border, Color, Element, Length, Pixels, Point, Size, Task, Theme};
+use std::collections::HashSet;
 use std::path::PathBuf;
 use std::sync::Arc;
 use std::sync::atomic::Ordering;
+use std::time::Instant;
 
+use crate::details::{self, NodeDetails};
+use crate::duplicates::{self, DuplicateGroup, prune_groups};
+use crate::empty::{EmptyReport, find_empty};
+use crate::extensions::{
+    ExtensionColumn, ExtensionStats, extension_stats, files_with_extension, sort_stats,
+};
+use crate::listing::{ListRow, SortColumn, list_children, list_nodes, sort_rows};
+use crate::node_color::{ColorMode, Colorizer, Vision, label_color, now_secs, owner_color};
+use crate::old::{DAY, OldFile, OldQuery, find_old_files};
+use crate::owners::{OwnerSummary, group_name, owner_summary, user_name};
+use crate::reclaimable::{RuleMatches, find_reclaimable};
 use crate::scan::Scan;
+use crate::settings::Settings;
+use crate::sizes::{SizeMode, Sizes, filtered_sizes};
 use crate::squarify::compute_gui_nodes;
-use crate::types::{GUINode, NodeID, Rectangle};
-use crate::{actions, config};
+use crate::state::{AppState, WindowState};
+use crate::sunburst::{SunburstNode, compute_sunburst_nodes};
+use crate::theme::{AppTheme, ThemeColors};
+use crate::types::{GUINode, NodeID, Rectangle, Tree};
+use crate::utils::{age_display, bytes_display, count_display};
+use crate::{actions, config, cushion, icicle};
 
 #[derive(Debug, Clone)]
 pub enum TreeMapMessage {
@@ -23,23 +52,126 @@ pub enum TreeMapMessage {
     RecalculateRects,
     BoundsChanged(iced::Rectangle),
     NodeHovered(Option<NodeID>),
+    // the block of smaller items in the directory, see GUINode::small_items
+    SmallItemsHovered(NodeID),
+    SmallItemsOpened(NodeID),
     NodeRightClicked { node_id: NodeID, position: Point },
     CloseContextMenu,
     ExecuteAction(String, NodeID),
     FocusOnActiveNode,
     FocusOnRootNode,
     FocusOnPreviousNode,
+    FocusOnNextNode,
+    BreadcrumbClicked(NodeID),
     ScanRestarted,
     Ignore,
     PromptTrashNode(NodeID),
+    PromptTrashNodes(Vec<NodeID>, String),
     ConfirmTrashNode,
     CancelTrashNode,
+    // the paths that couldn't be trashed, with why
+    TrashFinished(Vec<(String, String)>),
+    TrashFailuresDismissed,
     EscPressed,
+    ColorModeSelected(ColorMode),
+    SizeModeSelected(SizeMode),
+    PanelSelected(panel::Panel),
+    PanelClosed,
+    OwnerFilterSelected(Option<u32>),
+    OwnerFilterCleared,
+    ThemeSelected(AppTheme),
+    VisionSelected(Vision),
+    CuesToggled(bool),
+    CushionsToggled(bool),
+    CushionRendered(CushionKey, image::Handle),
+    ViewModeSelected(ViewMode),
+    AnimationTick(iced::time::Instant),
+    WheelZoomed { factor: f32, anchor: Point },
+    Panned(f32, f32),
+    ZoomReset,
+    ListSortSelected(SortColumn),
+    ListRowSelected(NodeID),
+    ListRowOpened(NodeID),
+    SelectionMoved(Direction),
+    SelectionOpened,
+    ContextMenuMoved(isize),
+    PanelResizeStarted,
+    PanelResized(f32),
+    PanelResizeEnded,
+    DuplicatesSearched,
+    ReclaimRuleToggled(String, bool),
+    ReclaimSelectedTrashed,
+    EmptyItemsTrashed,
+    // shows the directory the node is in, with the node selected
+    NodeLocated(NodeID),
+    // in months, and an index into panel::OLD_SIZE_STEPS
+    OldAgeChanged(u8),
+    OldSizeChanged(u8),
+    OldFilterToggled(bool),
+    ExtensionSortSelected(ExtensionColumn),
+    SettingsOpened,
+    SettingsEdited(settings_dialog::SettingsField, String),
+    SettingsCrossDeviceToggled(bool),
+    SettingsSaved,
+    SettingsCancelled,
+    WindowResized(Size),
+    WindowMoved(Point),
+    WindowCloseRequested(iced::window::Id),
+    // selecting the selected extension again clears it
+    ExtensionSelected(String),
+    // the scan they were found in, results of an earlier scan are dropped
+    DuplicatesFound(Arc<Scan>, Vec<DuplicateGroup>),
+}
+
+// the shown root, the canvas size and the pan and zoom (as bits) a cushion image was rendered for
+type CushionKey = (NodeID, u32, u32, [u32; 3]);
+
+// the shown root, panel, color mode, old files query and marked extension the reports were made for
+type ReportsKey = (
+    NodeID,
+    Option<panel::Panel>,
+    ColorMode,
+    OldQuery,
+    Option<String>,
+);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ViewMode {
+    #[default]
+    Treemap,
+    Sunburst,
+    Icicle,
+}
+
+impl ViewMode {
+    pub const ALL: [ViewMode; 3] = [ViewMode::Treemap, ViewMode::Sunburst, ViewMode::Icicle];
+}
+
+impl std::fmt::Display for ViewMode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ViewMode::Treemap => write!(f, "Treemap"),
+            ViewMode::Sunburst => write!(f, "Sunburst"),
+            ViewMode::Icicle => write!(f, "Icicle"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Direction {
+    Up,
+    Down,
+    Left,
+    Right,
 }
 
-// storing nothing for now
 #[derive(Default)]
-pub struct TreeMapState {}
+pub struct TreeMapState {
+    // to only report leaving the canvas once, other widgets set the hover too
+    cursor_inside: bool,
+    // where the cursor was while dragging with the middle button
+    pan_from: Option<Point>,
+}
 
 pub struct TreeMapProgram {
     pub rects_cache: canvas::Cache,
@@ -48,15 +180,262 @@ pub struct TreeMapProgram {
     pub bounds: iced::Rectangle,
     pub active_node: Option<NodeID>,
     pub active_node_is_stale: bool,
+    // the keyboard selection, also picked from the list panel
+    // kept apart from active_node, which follows the mouse
+    pub selected_node: Option<NodeID>,
+    // the directory whose block of smaller items is hovered, active_node is None then
+    pub hovered_small_items: Option<NodeID>,
     pub context_menu: Option<context_menu::ContextMenu>,
+    pub colors: &'static ThemeColors,
+    pub cues: bool,
+    // shaded rendering of gui_nodes, replaces the flat rectangles when present
+    pub cushion: Option<image::Handle>,
+    pub view: ViewMode,
+    pub sunburst_nodes: Vec<SunburstNode>,
+    // running while the view zooms into or out of a directory
+    pub zoom: Option<zoom::ZoomAnimation>,
+    // only used by the treemap view
+    pub pan_zoom: zoom::PanZoom,
+    // get a dashed outline, such as the duplicates or the reclaimable directories
+    // while their panel is open
+    pub marked_nodes: HashSet<NodeID>,
+    // a dialog over the canvas takes the keyboard, only Escape gets through to close it
+    pub dialog_open: bool,
 }
 
 impl TreeMapProgram {
     fn locate_node(&self, point: Point) -> Option<NodeID> {
-        self.gui_nodes
+        match self.view {
+            ViewMode::Treemap | ViewMode::Icicle => self
+                .gui_nodes
+                .iter()
+                .rfind(|gnode| {
+                    gnode.small_items.is_empty() && gnode.rect.contains_point(point.x, point.y)
+                })
+                .map(|gnode| gnode.node_id),
+            ViewMode::Sunburst => {
+                sunburst::locate_node(&self.sunburst_nodes, self.bounds.size(), point)
+            }
+        }
+    }
+
+    fn hover_message(&self, point: Point) -> TreeMapMessage {
+        if let Some(gnode) = self
+            .gui_nodes
             .iter()
             .rfind(|gnode| gnode.rect.contains_point(point.x, point.y))
-            .map(|gnode| gnode.node_id)
+            && !gnode.small_items.is_empty()
+        {
+            return TreeMapMessage::SmallItemsHovered(gnode.node_id);
+        }
+        TreeMapMessage::NodeHovered(self.locate_node(point))
+    }
+
+    fn small_items_of(&self, node_id: NodeID) -> Option<&GUINode> {
+        self.gui_nodes
+            .iter()
+            .find(|x| x.node_id == node_id && !x.small_items.is_empty())
+    }
+
+    fn label_of(&self, node_id: NodeID) -> Option<String> {
+        match self.view {
+            ViewMode::Treemap | ViewMode::Icicle => self
+                .gui_nodes
+                .iter()
+                .find(|x| x.node_id == node_id)
+                .map(|x| x.label.clone()),
+            ViewMode::Sunburst => self
+                .sunburst_nodes
+                .iter()
+                .find(|x| x.node_id == node_id)
+                .map(|x| x.label.clone()),
+        }
+    }
+
+    // where the node is drawn, for moving the selection with the arrow keys
+    fn node_centers(&self) -> Vec<(NodeID, Point)> {
+        match self.view {
+            ViewMode::Treemap | ViewMode::Icicle => self
+                .gui_nodes
+                .iter()
+                .filter(|gnode| gnode.small_items.is_empty())
+                .map(|gnode| {
+                    let rect = gnode.rect;
+                    (
+                        gnode.node_id,
+                        Point::new(rect.x + rect.width / 2.0, rect.y + rect.height / 2.0),
+                    )
+                })
+                .collect(),
+            ViewMode::Sunburst => self
+                .sunburst_nodes
+                .iter()
+                .map(|node| {
+                    (
+                        node.node_id,
+                        sunburst::segment_center(node, self.bounds.size()),
+                    )
+                })
+                .collect(),
+        }
+    }
+
+    fn is_hovered(&self, gnode: &GUINode) -> bool {
+        if gnode.small_items.is_empty() {
+            self.active_node == Some(gnode.node_id)
+        } else {
+            self.hovered_small_items == Some(gnode.node_id)
+        }
+    }
+
+    fn is_highlighted(&self, gnode: &GUINode) -> bool {
+        self.is_hovered(gnode)
+            || (gnode.small_items.is_empty() && self.selected_node == Some(gnode.node_id))
+    }
+
+    // the layout fills the canvas, starting at the origin
+    fn base_rect(&self) -> Rectangle {
+        Rectangle {
+            x: 0.0,
+            y: 0.0,
+            width: self.bounds.width,
+            height: self.bounds.height,
+        }
+    }
+
+    // where a layout rectangle is drawn, which differs from the layout while zooming
+    fn drawn_rect(&self, rect: Rectangle) -> Rectangle {
+        match &self.zoom {
+            Some(zoom) => zoom.map(rect, self.base_rect()),
+            None => rect,
+        }
+    }
+
+    // the new layout will grow out of the node's current rectangle, if it's drawn
+    fn start_zoom_in(&mut self, node_id: NodeID) {
+        if let Some(gnode) = self.gui_nodes.iter().find(|x| x.node_id == node_id) {
+            self.zoom = Some(zoom::ZoomAnimation::zoom_in(gnode.rect, self.base_rect()));
+        }
+    }
+
+    fn draw_treemap(&self, frame: &mut canvas::Frame) {
+        // the cushion image is rendered for the final layout only
+        let cushion = self.cushion.as_ref().filter(|_| self.zoom.is_none());
+        if let Some(cushion) = cushion {
+            frame.draw_image(iced::Rectangle::new(Point::ORIGIN, frame.size()), cushion);
+        } else if !self.gui_nodes.is_empty() {
+            frame.fill_rectangle(Point::ORIGIN, frame.size(), Color::from(self.colors.border));
+        }
+        let border = config::tuning().border;
+        for gnode in self.gui_nodes.iter() {
+            let rect = self.drawn_rect(gnode.rect);
+            // zooming out blows the layout up beyond the canvas
+            if rect.x > frame.width()
+                || rect.y > frame.height()
+                || rect.x + rect.width < 0.0
+                || rect.y + rect.height < 0.0
+            {
+                continue;
+            }
+            let rect_pos = Point::new(rect.x + border, rect.y + border);
+            let rect_size = Size::new(rect.width - border * 2.0, rect.height - border * 2.0);
+
+            if cushion.is_none() {
+                let color = if self.is_highlighted(gnode) {
+                    Color::from(gnode.color).mix(Color::WHITE, 0.1)
+                } else {
+                    Color::from(gnode.color)
+                };
+                frame.fill_rectangle(rect_pos, rect_size, color);
+            }
+
+            let text_color = Color::from(label_color(gnode.color, self.colors.text));
+            if self.cues {
+                let line_dash = if gnode.is_file {
+                    canvas::LineDash {
+                        segments: &[4.0, 3.0],
+                        offset: 0,
+                    }
+                } else {
+                    canvas::LineDash::default()
+                };
+                frame.stroke_rectangle(
+                    rect_pos,
+                    rect_size,
+                    canvas::Stroke {
+                        width: 1.0,
+                        line_dash,
+                        ..Default::default()
+                    }
+                    .with_color(text_color),
+                );
+            }
+
+            frame.fill_text(canvas::Text {
+                content: gnode.label.clone(),
+                position: Point::new(rect.x + 2.0, rect.y + 2.0),
+                color: text_color,
+                max_width: rect.width,
+                wrapping: text::Wrapping::None,
+                ellipsis: text::Ellipsis::End,
+                size: Pixels(config::tuning().text_size),
+                ..Default::default()
+            });
+        }
+
+        for gnode in self
+            .gui_nodes
+            .iter()
+            .filter(|x| x.small_items.is_empty() && self.marked_nodes.contains(&x.node_id))
+        {
+            let rect = self.drawn_rect(gnode.rect);
+            frame.stroke_rectangle(
+                Point::new(rect.x + 1.5, rect.y + 1.5),
+                Size::new(rect.width - 3.0, rect.height - 3.0),
+                canvas::Stroke {
+                    width: 2.0,
+                    line_dash: canvas::LineDash {
+                        segments: &[6.0, 3.0],
+                        offset: 0,
+                    },
+                    ..Default::default()
+                }
+                .with_color(Color::from(self.colors.focus)),
+            );
+        }
+
+        // drawn last so that the children can't cover it
+        // the cushion image is shared by all nodes, so it can't be tinted for hover either
+        if (self.cues || cushion.is_some())
+            && let Some(gnode) = self.gui_nodes.iter().find(|x| self.is_hovered(x))
+        {
+            let rect = self.drawn_rect(gnode.rect);
+            frame.stroke_rectangle(
+                Point::new(rect.x + 1.5, rect.y + 1.5),
+                Size::new(rect.width - 3.0, rect.height - 3.0),
+                canvas::Stroke {
+                    width: 3.0,
+                    ..Default::default()
+                }
+                .with_color(Color::from(self.colors.text)),
+            );
+        }
+
+        if let Some(gnode) = self
+            .selected_node
+            .and_then(|id| self.gui_nodes.iter().find(|x| x.node_id == id))
+        {
+            let rect = self.drawn_rect(gnode.rect);
+            frame.stroke_rectangle(
+                Point::new(rect.x + 1.5, rect.y + 1.5),
+                Size::new(rect.width - 3.0, rect.height - 3.0),
+                canvas::Stroke {
+                    width: 3.0,
+                    ..Default::default()
+                }
+                .with_color(Color::from(self.colors.focus)),
+            );
+        }
     }
 }
 
@@ -65,13 +444,23 @@ impl Program<TreeMapMessage> for TreeMapProgram {
 
     fn update(
         &self,
-        _state: &mut Self::State,
+        state: &mut Self::State,
         event: &iced::Event,
         bounds: iced::Rectangle,
         cursor: mouse::Cursor,
     ) -> Option<canvas::Action<TreeMapMessage>> {
         let mut message = None;
 
+        if self.dialog_open {
+            return match event {
+                iced::Event::Keyboard(iced::keyboard::Event::KeyReleased {
+                    key: key::Key::Named(Escape),
+                    ..
+                }) => Some(canvas::Action::publish(TreeMapMessage::EscPressed)),
+                _ => None,
+            };
+        }
+
         if self.bounds != bounds {
             message = Some(TreeMapMessage::BoundsChanged(bounds));
         }
@@ -79,19 +468,29 @@ impl Program<TreeMapMessage> for TreeMapProgram {
         if self.active_node_is_stale {
             if let Some(position) = cursor.position_in(bounds) {
                 if self.context_menu.is_none() {
-                    let hovered = self.locate_node(position);
-                    message = Some(TreeMapMessage::NodeHovered(hovered));
+                    message = Some(self.hover_message(position));
                 }
             }
         }
 
         match event {
+            iced::Event::Mouse(mouse::Event::CursorMoved { position })
+                if state.pan_from.is_some() =>
+            {
+                if let Some(from) = state.pan_from.replace(*position) {
+                    message = Some(TreeMapMessage::Panned(
+                        position.x - from.x,
+                        position.y - from.y,
+                    ));
+                }
+            }
             iced::Event::Mouse(mouse::Event::CursorMoved { .. }) => {
                 if self.context_menu.is_none() {
                     if let Some(position) = cursor.position_in(bounds) {
-                        let hovered = self.locate_node(position);
-                        message = Some(TreeMapMessage::NodeHovered(hovered));
-                    } else {
+                        state.cursor_inside = true;
+                        message = Some(self.hover_message(position));
+                    } else if state.cursor_inside {
+                        state.cursor_inside = false;
                         message = Some(TreeMapMessage::NodeHovered(None));
                     }
                 } else {
@@ -121,10 +520,43 @@ impl Program<TreeMapMessage> for TreeMapProgram {
                     } else {
                         message = Some(TreeMapMessage::CloseContextMenu);
                     }
+                } else if let Some(id) = self.hovered_small_items {
+                    message = Some(TreeMapMessage::SmallItemsOpened(id));
                 } else {
                     message = Some(TreeMapMessage::FocusOnActiveNode);
                 }
             }
+            iced::Event::Mouse(mouse::Event::WheelScrolled { delta })
+                if self.view == ViewMode::Treemap && self.context_menu.is_none() =>
+            {
+                if let Some(anchor) = cursor.position_in(bounds) {
+                    let steps = match delta {
+                        mouse::ScrollDelta::Lines { y, .. } => *y,
+                        mouse::ScrollDelta::Pixels { y, .. } => *y / 50.0,
+                    };
+                    message = Some(TreeMapMessage::WheelZoomed {
+                        factor: 1.25_f32.powf(steps),
+                        anchor,
+                    });
+                }
+            }
+            iced::Event::Mouse(mouse::Event::ButtonPressed(mouse::Button::Middle))
+                if self.view == ViewMode::Treemap
+                    && self.context_menu.is_none()
+                    && cursor.is_over(bounds) =>
+            {
+                state.pan_from = cursor.position();
+            }
+            iced::Event::Mouse(mouse::Event::ButtonReleased(mouse::Button::Middle)) => {
+                state.pan_from = None;
+            }
+            // the extra mouse buttons go back and forth like in a browser
+            iced::Event::Mouse(mouse::Event::ButtonPressed(mouse::Button::Back)) => {
+                message = Some(TreeMapMessage::FocusOnPreviousNode);
+            }
+            iced::Event::Mouse(mouse::Event::ButtonPressed(mouse::Button::Forward)) => {
+                message = Some(TreeMapMessage::FocusOnNextNode);
+            }
             iced::Event::Keyboard(iced::keyboard::Event::KeyReleased { key, .. }) => match key {
                 key::Key::Named(Escape) => {
                     message = Some(TreeMapMessage::EscPressed);
@@ -134,6 +566,85 @@ impl Program<TreeMapMessage> for TreeMapProgram {
                 }
                 _ => {}
             },
+            iced::Event::Keyboard(iced::keyboard::Event::KeyPressed { key, modifiers, .. }) => {
+                if let Some(menu) = &self.context_menu {
+                    // the arrow keys pick an item of the open menu instead
+                    match key {
+                        key::Key::Named(ArrowUp) => {
+                            message = Some(TreeMapMessage::ContextMenuMoved(-1));
+                        }
+                        key::Key::Named(ArrowDown) => {
+                            message = Some(TreeMapMessage::ContextMenuMoved(1));
+                        }
+                        key::Key::Named(Enter) => {
+                            message = menu.get_selected_action().map(|action| {
+                                TreeMapMessage::ExecuteAction(action, menu.target_node)
+                            });
+                        }
+                        _ => {}
+                    }
+                } else {
+                    match key {
+                        key::Key::Named(ArrowLeft) if modifiers.alt() => {
+                            message = Some(TreeMapMessage::FocusOnPreviousNode);
+                        }
+                        key::Key::Named(ArrowRight) if modifiers.alt() => {
+                            message = Some(TreeMapMessage::FocusOnNextNode);
+                        }
+                        key::Key::Named(ArrowUp) => {
+                            message = Some(TreeMapMessage::SelectionMoved(Direction::Up));
+                        }
+                        key::Key::Named(ArrowDown) => {
+                            message = Some(TreeMapMessage::SelectionMoved(Direction::Down));
+                        }
+                        key::Key::Named(ArrowLeft) => {
+                            message = Some(TreeMapMessage::SelectionMoved(Direction::Left));
+                        }
+                        key::Key::Named(ArrowRight) => {
+                            message = Some(TreeMapMessage::SelectionMoved(Direction::Right));
+                        }
+                        key::Key::Named(Enter) => {
+                            message = Some(TreeMapMessage::SelectionOpened);
+                        }
+                        key::Key::Named(ContextMenu) | key::Key::Named(F10)
+                            if *key == key::Key::Named(ContextMenu) || modifiers.shift() =>
+                        {
+                            // open the menu in the middle of the selection, but fully inside the canvas
+                            if let Some(node_id) = self.selected_node
+                                && let Some((_, center)) = self
+                                    .node_centers()
+                                    .into_iter()
+                                    .find(|(id, _)| *id == node_id)
+                            {
+                                let position = Point::new(
+                                    center
+                                        .x
+                                        .min(bounds.width - context_menu::MENU_WIDTH)
+                                        .max(0.0),
+                                    center
+                                        .y
+                                        .min(bounds.height - context_menu::menu_height())
+                                        .max(0.0),
+                                );
+                                message =
+                                    Some(TreeMapMessage::NodeRightClicked { node_id, position });
+                            }
+                        }
+                        key::Key::Named(Delete) => {
+                            message = self.selected_node.map(TreeMapMessage::PromptTrashNode);
+                        }
+                        key::Key::Character(c) if c.as_str() == "0" => {
+                            message = Some(TreeMapMessage::ZoomReset);
+                        }
+                        key::Key::Character(c) if c.as_str() == "o" => {
+                            message = self
+                                .selected_node
+                                .map(|id| TreeMapMessage::ExecuteAction(String::from("Show"), id));
+                        }
+                        _ => {}
+                    }
+                }
+            }
             _ => {}
         }
 
@@ -148,42 +659,24 @@ impl Program<TreeMapMessage> for TreeMapProgram {
         bounds: iced::Rectangle,
         cursor: mouse::Cursor,
     ) -> Vec<Geometry> {
-        let tree_geometry = self.rects_cache.draw(renderer, bounds.size(), |frame| {
-            for gnode in self.gui_nodes.iter() {
-                let color_rgba = gnode.color;
-
-                let color = if self.active_node.is_some_and(|x| x == gnode.node_id) {
-                    Color::from_rgba(color_rgba.r, color_rgba.g, color_rgba.b, color_rgba.a)
-                        .mix(Color::WHITE, 0.1)
-                } else {
-                    Color::from_rgba(color_rgba.r, color_rgba.g, color_rgba.b, color_rgba.a)
-                };
-
-                let rect_pos =
-                    Point::new(gnode.rect.x + config::BORDER, gnode.rect.y + config::BORDER);
-                let rect_size = Size::new(
-                    gnode.rect.width - config::BORDER * 2.0,
-                    gnode.rect.height - config::BORDER * 2.0,
-                );
-
-                frame.fill_rectangle(rect_pos, rect_size, color);
-
-                frame.fill_text(canvas::Text {
-                    content: gnode.label.clone(),
-                    position: Point::new(gnode.rect.x + 2.0, gnode.rect.y + 2.0),
-                    color: Color::BLACK,
-                    max_width: gnode.rect.width,
-                    wrapping: text::Wrapping::None,
-                    ellipsis: text::Ellipsis::End,
-                    size: Pixels(config::TEXT_SIZE),
-                    ..Default::default()
+        let tree_geometry =
+            self.rects_cache
+                .draw(renderer, bounds.size(), |frame| match self.view {
+                    // icicle rows are rectangles as well
+                    ViewMode::Treemap | ViewMode::Icicle => self.draw_treemap(frame),
+                    ViewMode::Sunburst => sunburst::draw(
+                        frame,
+                        &self.sunburst_nodes,
+                        self.active_node,
+                        self.selected_node,
+                        self.colors,
+                        self.cues,
+                    ),
                 });
-            }
-        });
 
         if let Some(menu) = &self.context_menu {
             let menu_geometry = self.menu_cache.draw(renderer, bounds.size(), |frame| {
-                menu.draw(frame, cursor.position_in(bounds));
+                menu.draw(frame, cursor.position_in(bounds), self.colors);
             });
             return vec![tree_geometry, menu_geometry];
         }
@@ -195,13 +688,72 @@ struct TreeMapApp {
     scan: Option<Arc<Scan>>,
     program: TreeMapProgram,
     scan_progress: f32,
-    node_pending_trash: Option<GUINode>,
+    // the nodes and a label for them
+    nodes_pending_trash: Option<(Vec<NodeID>, String)>,
+    // shown until dismissed, the rest was trashed
+    trash_failures: Vec<(String, String)>,
     shown_root_id_history: Vec<NodeID>,
     shown_root_path_history: Vec<String>,
+    // what Back left, with the paths, most recent last
+    shown_root_forward: Vec<(NodeID, String)>,
+    // the shown root we left for one of its ancestors, the zoom out starts from its rectangle
+    // once the new layout is there
+    zoom_out_from: Option<NodeID>,
+    color_mode: ColorMode,
+    size_mode: SizeMode,
+    panel: Option<panel::Panel>,
+    owner_summary: Vec<OwnerSummary>,
+    // Some(uid) when only the files of one owner are shown
+    owner_filter: Option<Option<u32>>,
+    settings: Settings,
+    // the key of program.cushion, and the state of the render running in the background
+    cushion_key: Option<CushionKey>,
+    cushion_rendering: bool,
+    cushion_outdated: bool,
+    // what the owner summary and the panels below were last made for, and when
+    // they're made again when that changes, and at most every REPORTS_PERIOD as the scan goes on
+    reports_key: Option<ReportsKey>,
+    reports_updated: Instant,
+    tree_changed: bool,
+    // children of the shown root for the list panel, and the column and direction they're sorted by
+    // or of the block of smaller items that was clicked, with the directory they're in
+    list_rows: Vec<ListRow>,
+    small_items: Option<(NodeID, Vec<NodeID>)>,
+    list_sort: (SortColumn, bool),
+    panel_width: f32,
+    // the right edge of the panel while its handle is dragged
+    panel_resize_edge: Option<f32>,
+    // None until searched for in this scan
+    duplicates: Option<Vec<DuplicateGroup>>,
+    duplicates_searching: bool,
+    // build outputs and caches under the shown root, and the names of the rules ticked
+    reclaimable: Vec<RuleMatches>,
+    reclaim_selected: HashSet<String>,
+    // empty directories and zero-byte files under the shown root
+    empty: EmptyReport,
+    // (node, size, path) of the largest files in the whole scan
+    largest_files: Vec<(NodeID, u64, String)>,
+    // files unused for a while under the shown root, and whether only they are shown
+    old_query: OldQuery,
+    old_filter: bool,
+    old_files: Vec<OldFile>,
+    // files under the shown root by extension, how they're sorted, and the one marked
+    extension_stats: Vec<ExtensionStats>,
+    extension_sort: (ExtensionColumn, bool),
+    selected_extension: Option<String>,
+    // the settings dialog while it's open, and what was wrong with the settings file
+    settings_draft: Option<settings_dialog::SettingsDraft>,
+    settings_errors: Vec<String>,
+    // recent scans and the window geometry, saved when the window closes
+    state: AppState,
+    // the recent scans whose directories are still there, for the start screen
+    existing_roots: Vec<PathBuf>,
 }
 
 impl TreeMapApp {
-    fn new(start_with_scan: Option<PathBuf>) -> (Self, Task<TreeMapMessage>) {
+    fn new(start_with_scan: Option<PathBuf>, state: AppState) -> (Self, Task<TreeMapMessage>) {
+        let (settings, settings_errors) = Settings::load();
+        config::set_tuning(settings.tuning);
         (
             Self {
                 scan: None,
@@ -218,11 +770,58 @@ impl TreeMapApp {
                     },
                     active_node: None,
                     active_node_is_stale: false,
+                    selected_node: None,
+                    hovered_small_items: None,
                     context_menu: None,
+                    colors: settings.theme.colors(),
+                    cues: settings.cues,
+                    cushion: None,
+                    view: ViewMode::default(),
+                    sunburst_nodes: vec![],
+                    zoom: None,
+                    pan_zoom: zoom::PanZoom::new(0),
+                    marked_nodes: HashSet::new(),
+                    dialog_open: false,
                 },
-                node_pending_trash: None,
+                nodes_pending_trash: None,
+                trash_failures: vec![],
                 shown_root_id_history: vec![],
                 shown_root_path_history: vec![],
+                shown_root_forward: vec![],
+                zoom_out_from: None,
+                color_mode: ColorMode::default(),
+                size_mode: settings.size_mode,
+                panel: None,
+                owner_summary: vec![],
+                owner_filter: None,
+                settings,
+                cushion_key: None,
+                cushion_rendering: false,
+                cushion_outdated: false,
+                reports_key: None,
+                reports_updated: Instant::now(),
+                tree_changed: true,
+                list_rows: vec![],
+                small_items: None,
+                list_sort: (SortColumn::default(), true),
+                panel_width: panel::PANEL_WIDTH,
+                panel_resize_edge: None,
+                duplicates: None,
+                duplicates_searching: false,
+                reclaimable: vec![],
+                reclaim_selected: HashSet::new(),
+                empty: EmptyReport::default(),
+                largest_files: vec![],
+                old_query: OldQuery::default(),
+                old_filter: false,
+                old_files: vec![],
+                extension_stats: vec![],
+                extension_sort: (ExtensionColumn::default(), true),
+                selected_extension: None,
+                settings_draft: None,
+                settings_errors,
+                existing_roots: state.existing_roots().cloned().collect(),
+                state,
             },
             Task::done(TreeMapMessage::FolderSelected(start_with_scan.to_owned())),
         )
@@ -248,22 +847,33 @@ impl TreeMapApp {
             }
             TreeMapMessage::FolderSelected(path) => {
                 if let Some(path) = path {
+                    self.state.add_recent_root(&path);
+                    self.existing_roots = self.state.existing_roots().cloned().collect();
+                    self.save_state();
                     let path_str = path.to_string_lossy().into_owned();
-                    self.scan = Some(Arc::new(Scan::new(&path_str)));
+                    self.scan = Some(Arc::new(Scan::new(&path_str, self.settings.scan_options())));
 
                     self.shown_root_path_history.clear();
                     self.shown_root_path_history.push(path_str);
                     self.shown_root_id_history.clear();
+                    self.shown_root_forward.clear();
+                    self.owner_filter = None;
+                    self.old_filter = false;
+                    self.duplicates = None;
+                    self.duplicates_searching = false;
                     return Task::done(TreeMapMessage::RecalculateRects);
                 }
             }
             TreeMapMessage::ScanRestarted => {
                 if let Some(scan) = &self.scan {
                     let path = scan.path.clone();
-                    self.scan = Some(Arc::new(Scan::new(&path)));
+                    self.scan = Some(Arc::new(Scan::new(&path, self.settings.scan_options())));
 
                     self.shown_root_id_history.clear();
                     self.shown_root_path_history.truncate(1); // only keep the root path
+                    self.shown_root_forward.clear();
+                    self.duplicates = None;
+                    self.duplicates_searching = false;
                     return Task::done(TreeMapMessage::RecalculateRects);
                 }
             }
@@ -271,46 +881,171 @@ impl TreeMapApp {
                 if let Some(scan) = &self.scan {
                     if scan.update_signal.load(Ordering::SeqCst) {
                         scan.update_signal.store(false, Ordering::SeqCst);
+                        self.tree_changed = true;
                         self.scan_progress = scan.progress() as f32;
                         return Task::done(TreeMapMessage::RecalculateRects);
                     }
                 }
             }
             TreeMapMessage::RecalculateRects => {
-                let base_rect = Rectangle {
-                    x: 0.0,
-                    y: 0.0,
-                    width: self.program.bounds.width,
-                    height: self.program.bounds.height,
-                };
+                let base_rect = self.program.base_rect();
                 self.program.gui_nodes.clear();
+                self.program.sunburst_nodes.clear();
 
-                if let Some(scan) = &self.scan {
+                // cloned so that the reports can be updated while the tree is locked
+                if let Some(scan) = self.scan.clone() {
                     if let Ok(tree) = scan.tree_mutex.lock() {
                         let shown_root = *self.shown_root_id_history.last().unwrap_or(&0_usize);
-                        self.program
-                            .gui_nodes
-                            .append(&mut compute_gui_nodes(&tree, shown_root, base_rect, 20.0));
+                        let colorizer = Colorizer::new(
+                            self.color_mode,
+                            self.program.colors,
+                            self.settings.vision,
+                        );
+                        let now = now_secs();
+                        let (owner_filter, old_filter) = (self.owner_filter, self.old_filter);
+                        let old_query = self.old_query;
+                        let sizes = Sizes {
+                            mode: self.size_mode,
+                            values: (owner_filter.is_some() || old_filter).then(|| {
+                                filtered_sizes(&tree, self.size_mode, |node| {
+                                    owner_filter.is_none_or(|uid| node.uid == uid)
+                                        && (!old_filter || old_query.matches(node, now))
+                                })
+                            }),
+                        };
+                        if self.program.pan_zoom.root != shown_root {
+                            self.program.pan_zoom = zoom::PanZoom::new(shown_root);
+                        }
+                        match self.program.view {
+                            ViewMode::Treemap => {
+                                self.program.gui_nodes.append(&mut compute_gui_nodes(
+                                    &tree,
+                                    shown_root,
+                                    self.program
+                                        .pan_zoom
+                                        .layout_rect(self.program.bounds.size()),
+                                    base_rect,
+                                    20.0,
+                                    &colorizer,
+                                    &sizes,
+                                ))
+                            }
+                            ViewMode::Icicle => {
+                                self.program.gui_nodes = icicle::compute_gui_nodes(
+                                    &tree, shown_root, base_rect, &colorizer, &sizes,
+                                )
+                            }
+                            ViewMode::Sunburst => {
+                                self.program.sunburst_nodes =
+                                    compute_sunburst_nodes(&tree, shown_root, &colorizer, &sizes)
+                            }
+                        }
+                        match (self.panel, &self.small_items) {
+                            (Some(panel::Panel::List), _) => {
+                                self.list_rows = list_children(&tree, shown_root, &sizes);
+                            }
+                            (Some(panel::Panel::SmallItems), Some((parent, ids))) => {
+                                self.list_rows = list_nodes(&tree, ids, *parent, &sizes);
+                            }
+                            _ => {}
+                        }
+                        sort_rows(&mut self.list_rows, self.list_sort.0, self.list_sort.1);
+                        if self.panel == Some(panel::Panel::Largest) {
+                            self.largest_files = tree
+                                .largest_files
+                                .sorted()
+                                .into_iter()
+                                .filter(|(_, id)| tree.is_attached(*id))
+                                .map(|(size, id)| {
+                                    let path = tree.get_elem(id).path.to_string_lossy().into();
+                                    (id, size, path)
+                                })
+                                .collect();
+                        }
+                        let reports_key = (
+                            shown_root,
+                            self.panel,
+                            self.color_mode,
+                            self.old_query,
+                            self.selected_extension.clone(),
+                        );
+                        let complete = scan.complete.load(Ordering::SeqCst);
+                        if self.reports_key.as_ref() != Some(&reports_key)
+                            || (self.tree_changed
+                                && (complete
+                                    || self.reports_updated.elapsed() >= config::REPORTS_PERIOD))
+                        {
+                            self.update_reports(&tree, shown_root, now);
+                            self.reports_key = Some(reports_key);
+                            self.reports_updated = Instant::now();
+                            self.tree_changed = false;
+                        }
+                        if let Some(groups) = &mut self.duplicates {
+                            prune_groups(&tree, groups);
+                            if self.panel == Some(panel::Panel::Duplicates) {
+                                self.program.marked_nodes = groups
+                                    .iter()
+                                    .flat_map(|g| g.nodes.iter().map(|(id, _)| *id))
+                                    .collect();
+                            }
+                        }
+                        // the selection only makes sense among the children of the shown root
+                        // or among the smaller items listed
+                        if let Some(id) = self.program.selected_node
+                            && tree.get_elem(id).parent != Some(shown_root)
+                            && !self.shows_small_item(id)
+                        {
+                            self.program.selected_node = None;
+                        }
                     }
                 }
+                if let Some(id) = self.zoom_out_from.take()
+                    && let Some(gnode) = self.program.gui_nodes.iter().find(|x| x.node_id == id)
+                {
+                    self.program.zoom = Some(zoom::ZoomAnimation::zoom_out(gnode.rect));
+                }
                 self.program.active_node_is_stale = true;
                 self.program.rects_cache.clear();
+                if self.cushion_key != Some(self.current_cushion_key()) {
+                    // don't show a cushion of another directory, or of another size
+                    self.program.cushion = None;
+                }
+                return self.render_cushion();
             }
             TreeMapMessage::BoundsChanged(bounds) => {
                 self.program.bounds = bounds;
+                self.program.pan_zoom = zoom::PanZoom::new(self.program.pan_zoom.root);
                 if self.scan.is_some() {
                     return Task::done(TreeMapMessage::RecalculateRects);
                 }
             }
             TreeMapMessage::NodeHovered(node_id) => {
-                if self.node_pending_trash.is_none() {
+                if self.nodes_pending_trash.is_none() {
                     self.program.active_node = node_id;
+                    self.program.hovered_small_items = None;
                     self.program.active_node_is_stale = false;
                     self.program.rects_cache.clear();
                 }
             }
+            TreeMapMessage::SmallItemsHovered(node_id) => {
+                if self.nodes_pending_trash.is_none() {
+                    self.program.active_node = None;
+                    self.program.hovered_small_items = Some(node_id);
+                    self.program.active_node_is_stale = false;
+                    self.program.rects_cache.clear();
+                }
+            }
+            TreeMapMessage::SmallItemsOpened(node_id) => {
+                if self.nodes_pending_trash.is_none()
+                    && let Some(gnode) = self.program.small_items_of(node_id)
+                {
+                    self.small_items = Some((node_id, gnode.small_items.clone()));
+                    self.panel = Some(panel::Panel::SmallItems);
+                    return Task::done(TreeMapMessage::RecalculateRects);
+                }
+            }
             TreeMapMessage::NodeRightClicked { node_id, position } => {
-                if self.node_pending_trash.is_none() {
+                if self.nodes_pending_trash.is_none() {
                     self.program.context_menu =
                         Some(context_menu::ContextMenu::new(node_id, position));
                     self.program.menu_cache.clear();
@@ -340,63 +1075,424 @@ impl TreeMapApp {
                 }
             }
             TreeMapMessage::PromptTrashNode(node_id) => {
-                self.node_pending_trash = self
+                // the node may not be drawn, when trashed from a panel
+                self.nodes_pending_trash = self
                     .program
-                    .gui_nodes
-                    .iter()
-                    .find(|x| node_id == x.node_id)
-                    .cloned();
+                    .label_of(node_id)
+                    .or_else(|| self.node_label(node_id))
+                    .map(|label| (vec![node_id], label));
+            }
+            TreeMapMessage::PromptTrashNodes(node_ids, label) => {
+                if !node_ids.is_empty() {
+                    self.nodes_pending_trash = Some((node_ids, label));
+                }
             }
             TreeMapMessage::CancelTrashNode => {
-                self.node_pending_trash = None;
+                self.nodes_pending_trash = None;
                 self.program.active_node_is_stale = true; // resets hover safety
             }
             TreeMapMessage::ConfirmTrashNode => {
-                if let (Some(scan), Some(gnode)) = (&self.scan, self.node_pending_trash.take()) {
-                    return Task::perform(actions::trash_node(scan.clone(), gnode.node_id), |_| {
-                        TreeMapMessage::RecalculateRects
-                    });
+                if let (Some(scan), Some((node_ids, _))) =
+                    (&self.scan, self.nodes_pending_trash.take())
+                {
+                    return Task::perform(
+                        actions::trash_nodes(scan.clone(), node_ids),
+                        TreeMapMessage::TrashFinished,
+                    );
                 }
             }
+            TreeMapMessage::TrashFinished(failures) => {
+                self.tree_changed = true;
+                if !failures.is_empty() {
+                    self.trash_failures = failures;
+                    self.program.dialog_open = true;
+                }
+                return Task::done(TreeMapMessage::RecalculateRects);
+            }
+            TreeMapMessage::TrashFailuresDismissed => {
+                self.trash_failures.clear();
+                self.program.dialog_open = self.settings_draft.is_some();
+            }
             TreeMapMessage::FocusOnActiveNode => {
-                // don't focus on the same node again
-                if self.node_pending_trash.is_none()
-                    && *self.shown_root_id_history.last().unwrap_or(&0_usize)
-                        != self.program.active_node.unwrap_or(0_usize)
-                {
-                    if let Some(id) = self.program.active_node
-                        && let Some(scan) = &self.scan
-                        && let Ok(tree) = scan.tree_mutex.lock()
-                    {
-                        let node = tree.get_elem(id);
-                        if !node.is_file {
-                            self.shown_root_id_history.push(node.id);
-                            self.shown_root_path_history
-                                .push(node.path.to_string_lossy().into());
-                            return Task::done(TreeMapMessage::RecalculateRects);
-                        }
-                    }
+                if let Some(id) = self.program.active_node {
+                    return self.focus_on_node(id);
                 }
             }
             TreeMapMessage::EscPressed => {
-                if self.node_pending_trash.is_some() {
+                if self.nodes_pending_trash.is_some() {
                     return Task::done(TreeMapMessage::CancelTrashNode);
+                } else if !self.trash_failures.is_empty() {
+                    return Task::done(TreeMapMessage::TrashFailuresDismissed);
+                } else if self.settings_draft.is_some() {
+                    return Task::done(TreeMapMessage::SettingsCancelled);
+                } else if self.program.context_menu.is_some() {
+                    return Task::done(TreeMapMessage::CloseContextMenu);
                 } else {
                     return Task::done(TreeMapMessage::FocusOnRootNode);
                 }
             }
             TreeMapMessage::FocusOnRootNode => {
+                self.zoom_out_from = self.shown_root_id_history.last().copied();
+                self.shown_root_forward.clear();
                 self.shown_root_id_history.clear();
                 self.shown_root_path_history.truncate(1); // only keep root path
                 return Task::done(TreeMapMessage::RecalculateRects);
             }
             TreeMapMessage::FocusOnPreviousNode => {
-                self.shown_root_id_history.pop();
-                if self.shown_root_path_history.len() > 1 {
-                    self.shown_root_path_history.pop();
+                if self.nodes_pending_trash.is_some() {
+                    return Task::none();
+                }
+                // keep the directory we come back from selected, to carry on from there
+                self.program.selected_node = self.shown_root_id_history.pop();
+                self.zoom_out_from = self.program.selected_node;
+                if let Some(id) = self.program.selected_node
+                    && self.shown_root_path_history.len() > 1
+                    && let Some(path) = self.shown_root_path_history.pop()
+                {
+                    self.shown_root_forward.push((id, path));
+                }
+                return Task::done(TreeMapMessage::RecalculateRects);
+            }
+            TreeMapMessage::FocusOnNextNode => {
+                if self.nodes_pending_trash.is_none()
+                    && let Some((id, path)) = self.shown_root_forward.pop()
+                {
+                    self.program.start_zoom_in(id);
+                    self.shown_root_id_history.push(id);
+                    self.shown_root_path_history.push(path);
+                    return Task::done(TreeMapMessage::RecalculateRects);
+                }
+            }
+            TreeMapMessage::BreadcrumbClicked(node_id) => {
+                return self.jump_to_node(node_id);
+            }
+            TreeMapMessage::ColorModeSelected(mode) => {
+                self.color_mode = mode;
+                return Task::done(TreeMapMessage::RecalculateRects);
+            }
+            TreeMapMessage::SizeModeSelected(mode) => {
+                self.size_mode = mode;
+                self.settings.size_mode = mode;
+                self.save_settings();
+                return Task::done(TreeMapMessage::RecalculateRects);
+            }
+            TreeMapMessage::PanelSelected(panel) => {
+                self.panel = Some(panel);
+                return Task::done(TreeMapMessage::RecalculateRects);
+            }
+            TreeMapMessage::PanelClosed => {
+                self.panel = None;
+                if !self.program.marked_nodes.is_empty() {
+                    self.program.marked_nodes.clear();
+                    self.program.rects_cache.clear();
+                }
+            }
+            TreeMapMessage::DuplicatesSearched => {
+                if let Some(scan) = &self.scan
+                    && scan.complete.load(Ordering::SeqCst)
+                    && !self.duplicates_searching
+                {
+                    self.duplicates_searching = true;
+                    let scan = scan.clone();
+                    return Task::perform(
+                        duplicates::find_duplicates(scan.clone()),
+                        move |groups| TreeMapMessage::DuplicatesFound(scan.clone(), groups),
+                    );
+                }
+            }
+            TreeMapMessage::ReclaimRuleToggled(name, on) => {
+                if on {
+                    self.reclaim_selected.insert(name);
+                } else {
+                    self.reclaim_selected.remove(&name);
+                }
+            }
+            TreeMapMessage::ReclaimSelectedTrashed => {
+                let selected: Vec<&RuleMatches> = self
+                    .reclaimable
+                    .iter()
+                    .filter(|m| self.reclaim_selected.contains(&m.name))
+                    .collect();
+                let node_ids = selected
+                    .iter()
+                    .flat_map(|m| m.dirs.iter().map(|d| d.node_id))
+                    .collect();
+                let label = format!(
+                    "{} ({})",
+                    selected
+                        .iter()
+                        .map(|m| m.name.as_str())
+                        .collect::<Vec<_>>()
+                        .join(", "),
+                    bytes_display(selected.iter().map(|m| m.size()).sum())
+                );
+                return Task::done(TreeMapMessage::PromptTrashNodes(node_ids, label));
+            }
+            TreeMapMessage::NodeLocated(node_id) => {
+                let parent = self.scan.as_ref().and_then(|scan| {
+                    let tree = scan.tree_mutex.lock().ok()?;
+                    tree.get_elem(node_id).parent
+                });
+                if let Some(parent) = parent {
+                    self.program.selected_node = Some(node_id);
+                    return self.jump_to_node(parent);
+                }
+            }
+            TreeMapMessage::SettingsOpened => {
+                self.settings_draft = Some(settings_dialog::SettingsDraft::new(
+                    &self.settings,
+                    self.settings_errors.clone(),
+                ));
+                self.program.dialog_open = true;
+            }
+            TreeMapMessage::SettingsEdited(field, value) => {
+                if let Some(draft) = &mut self.settings_draft {
+                    draft.edit(field, value);
                 }
+            }
+            TreeMapMessage::SettingsCrossDeviceToggled(on) => {
+                if let Some(draft) = &mut self.settings_draft {
+                    draft.cross_device = on;
+                }
+            }
+            TreeMapMessage::SettingsSaved => {
+                if let Some(draft) = &mut self.settings_draft {
+                    match draft.apply(&self.settings) {
+                        Ok(settings) => {
+                            self.settings = settings;
+                            self.settings_draft = None;
+                            self.program.dialog_open = false;
+                            config::set_tuning(self.settings.tuning);
+                            self.save_settings();
+                            self.program.menu_cache.clear();
+                            return Task::done(TreeMapMessage::RecalculateRects);
+                        }
+                        Err(errors) => draft.errors = errors,
+                    }
+                }
+            }
+            TreeMapMessage::WindowResized(size) => {
+                let window = self.state.window.get_or_insert(WindowState {
+                    width: size.width,
+                    height: size.height,
+                    x: None,
+                    y: None,
+                });
+                window.width = size.width;
+                window.height = size.height;
+            }
+            TreeMapMessage::WindowMoved(position) => {
+                // not resized yet, so still at the size it was opened with
+                let size = iced::window::Settings::default().size;
+                let window = self.state.window.get_or_insert(WindowState {
+                    width: size.width,
+                    height: size.height,
+                    x: None,
+                    y: None,
+                });
+                window.x = Some(position.x);
+                window.y = Some(position.y);
+            }
+            TreeMapMessage::WindowCloseRequested(id) => {
+                self.save_state();
+                return iced::window::close(id);
+            }
+            TreeMapMessage::SettingsCancelled => {
+                self.settings_draft = None;
+                self.program.dialog_open = false;
+            }
+            TreeMapMessage::ExtensionSortSelected(column) => {
+                self.extension_sort = if self.extension_sort.0 == column {
+                    (column, !self.extension_sort.1)
+                } else {
+                    (column, column != ExtensionColumn::Extension)
+                };
+                sort_stats(
+                    &mut self.extension_stats,
+                    self.extension_sort.0,
+                    self.extension_sort.1,
+                );
+            }
+            TreeMapMessage::ExtensionSelected(extension) => {
+                self.selected_extension = if self.selected_extension.as_ref() == Some(&extension) {
+                    None
+                } else {
+                    Some(extension)
+                };
+                return Task::done(TreeMapMessage::RecalculateRects);
+            }
+            TreeMapMessage::OldAgeChanged(months) => {
+                self.old_query.min_age = months as u64 * 30 * DAY;
+                return Task::done(TreeMapMessage::RecalculateRects);
+            }
+            TreeMapMessage::OldSizeChanged(step) => {
+                self.old_query.min_size = panel::OLD_SIZE_STEPS[step as usize];
                 return Task::done(TreeMapMessage::RecalculateRects);
             }
+            TreeMapMessage::OldFilterToggled(on) => {
+                self.old_filter = on;
+                return Task::done(TreeMapMessage::RecalculateRects);
+            }
+            TreeMapMessage::EmptyItemsTrashed => {
+                if !self
+                    .scan
+                    .as_ref()
+                    .is_some_and(|s| s.complete.load(Ordering::SeqCst))
+                {
+                    return Task::none();
+                }
+                let label = format!(
+                    "{} empty directories and {} zero-byte files",
+                    count_display(self.empty.dirs.len() as u64),
+                    count_display(self.empty.files.len() as u64)
+                );
+                return Task::done(TreeMapMessage::PromptTrashNodes(
+                    self.empty.node_ids(),
+                    label,
+                ));
+            }
+            TreeMapMessage::DuplicatesFound(scan, groups) => {
+                if self.scan.as_ref().is_some_and(|s| Arc::ptr_eq(s, &scan)) {
+                    self.duplicates_searching = false;
+                    self.duplicates = Some(groups);
+                    return Task::done(TreeMapMessage::RecalculateRects);
+                }
+            }
+            TreeMapMessage::OwnerFilterSelected(uid) => {
+                self.owner_filter = Some(uid);
+                return Task::done(TreeMapMessage::RecalculateRects);
+            }
+            TreeMapMessage::OwnerFilterCleared => {
+                self.owner_filter = None;
+                return Task::done(TreeMapMessage::RecalculateRects);
+            }
+            TreeMapMessage::ThemeSelected(theme) => {
+                self.settings.theme = theme;
+                self.program.colors = theme.colors();
+                self.program.menu_cache.clear();
+                self.save_settings();
+                return Task::done(TreeMapMessage::RecalculateRects);
+            }
+            TreeMapMessage::VisionSelected(vision) => {
+                self.settings.vision = vision;
+                self.save_settings();
+                return Task::done(TreeMapMessage::RecalculateRects);
+            }
+            TreeMapMessage::ViewModeSelected(view) => {
+                self.program.view = view;
+                self.program.context_menu = None;
+                // cushions are only rendered for the treemap
+                self.program.cushion = None;
+                self.cushion_key = None;
+                return Task::done(TreeMapMessage::RecalculateRects);
+            }
+            TreeMapMessage::CushionsToggled(cushions) => {
+                self.settings.cushions = cushions;
+                self.save_settings();
+                self.program.cushion = None;
+                self.cushion_key = None;
+                return Task::done(TreeMapMessage::RecalculateRects);
+            }
+            TreeMapMessage::CushionRendered(key, handle) => {
+                self.cushion_rendering = false;
+                if self.settings.cushions && key == self.current_cushion_key() {
+                    self.program.cushion = Some(handle);
+                    self.cushion_key = Some(key);
+                    self.program.rects_cache.clear();
+                }
+                if self.cushion_outdated {
+                    self.cushion_outdated = false;
+                    return self.render_cushion();
+                }
+            }
+            TreeMapMessage::ListSortSelected(column) => {
+                // clicking the sorted column again flips the direction
+                self.list_sort = if self.list_sort.0 == column {
+                    (column, !self.list_sort.1)
+                } else {
+                    (column, column != SortColumn::Name)
+                };
+                sort_rows(&mut self.list_rows, self.list_sort.0, self.list_sort.1);
+            }
+            TreeMapMessage::ListRowSelected(node_id) => {
+                self.program.selected_node = Some(node_id);
+                self.program.rects_cache.clear();
+            }
+            TreeMapMessage::ListRowOpened(node_id) => {
+                return self.focus_on_node(node_id);
+            }
+            TreeMapMessage::SelectionMoved(direction) => {
+                if self.nodes_pending_trash.is_some() {
+                    return Task::none();
+                }
+                // the list panels have their own order to follow
+                if matches!(
+                    self.panel,
+                    Some(panel::Panel::List | panel::Panel::SmallItems)
+                ) {
+                    match direction {
+                        Direction::Up => return self.move_list_selection(-1),
+                        Direction::Down => return self.move_list_selection(1),
+                        _ => {}
+                    }
+                }
+                self.move_selection(direction);
+            }
+            TreeMapMessage::SelectionOpened => {
+                if let Some(id) = self.program.selected_node {
+                    return self.focus_on_node(id);
+                }
+            }
+            TreeMapMessage::ContextMenuMoved(step) => {
+                if let Some(menu) = &mut self.program.context_menu {
+                    menu.move_selection(step);
+                    self.program.menu_cache.clear();
+                }
+            }
+            TreeMapMessage::PanelResizeStarted => {
+                self.panel_resize_edge = Some(
+                    self.program.bounds.x
+                        + self.program.bounds.width
+                        + 2.0 // the canvas padding
+                        + panel::RESIZE_HANDLE_WIDTH
+                        + self.panel_width,
+                );
+            }
+            TreeMapMessage::PanelResized(x) => {
+                if let Some(edge) = self.panel_resize_edge {
+                    self.panel_width =
+                        (edge - x).clamp(panel::MIN_PANEL_WIDTH, panel::MAX_PANEL_WIDTH);
+                }
+            }
+            TreeMapMessage::PanelResizeEnded => {
+                self.panel_resize_edge = None;
+            }
+            TreeMapMessage::AnimationTick(now) => {
+                if self.program.zoom.as_ref().is_some_and(|z| z.is_done(now)) {
+                    self.program.zoom = None;
+                }
+                self.program.rects_cache.clear();
+            }
+            TreeMapMessage::WheelZoomed { factor, anchor } => {
+                let size = self.program.bounds.size();
+                self.program.pan_zoom.zoom_at(factor, anchor, size);
+                return Task::done(TreeMapMessage::RecalculateRects);
+            }
+            TreeMapMessage::Panned(dx, dy) => {
+                let size = self.program.bounds.size();
+                self.program.pan_zoom.pan(dx, dy, size);
+                return Task::done(TreeMapMessage::RecalculateRects);
+            }
+            TreeMapMessage::ZoomReset => {
+                self.program.pan_zoom = zoom::PanZoom::new(self.program.pan_zoom.root);
+                return Task::done(TreeMapMessage::RecalculateRects);
+            }
+            TreeMapMessage::CuesToggled(cues) => {
+                self.settings.cues = cues;
+                self.program.cues = cues;
+                self.program.rects_cache.clear();
+                self.save_settings();
+            }
         }
         Task::none()
     }
@@ -420,6 +1516,14 @@ impl TreeMapApp {
                 .on_press(TreeMapMessage::FocusOnPreviousNode)
                 .into()
         };
+        let forward_button: Element<'_, TreeMapMessage> = if self.shown_root_forward.is_empty() {
+            text("").into()
+        } else {
+            button("Forward")
+                .style(button_style)
+                .on_press(TreeMapMessage::FocusOnNextNode)
+                .into()
+        };
 
         let header = column![
             container(
@@ -428,20 +1532,35 @@ impl TreeMapApp {
                         .style(button_style)
                         .on_press(TreeMapMessage::SelectFolder),
                     back_button,
-                    center_x(
-                        text(
-                            self.shown_root_path_history
-                                .last()
-                                .map(|x| x.as_str())
-                                .unwrap_or("SpaceMan")
-                        )
-                        .size(15.0)
-                        .font(iced::Font::DEFAULT.weight(iced::font::Weight::Bold))
-                        .align_y(iced::Alignment::Center)
-                    ),
+                    forward_button,
+                    center_x(self.breadcrumbs()),
+                    pick_list(
+                        ViewMode::ALL,
+                        Some(self.program.view),
+                        TreeMapMessage::ViewModeSelected
+                    )
+                    .text_size(14.0),
+                    pick_list(
+                        SizeMode::ALL,
+                        Some(self.size_mode),
+                        TreeMapMessage::SizeModeSelected
+                    )
+                    .text_size(14.0),
+                    pick_list(
+                        ColorMode::ALL,
+                        Some(self.color_mode),
+                        TreeMapMessage::ColorModeSelected
+                    )
+                    .text_size(14.0),
+                    pick_list(panel::Panel::ALL, self.panel, TreeMapMessage::PanelSelected)
+                        .placeholder("Panels")
+                        .text_size(14.0),
                     button("Refresh")
                         .style(button_style)
                         .on_press(TreeMapMessage::ScanRestarted),
+                    button("Settings")
+                        .style(button_style)
+                        .on_press(TreeMapMessage::SettingsOpened),
                 ]
                 .spacing(10)
                 .align_y(iced::Alignment::Center)
@@ -457,16 +1576,27 @@ impl TreeMapApp {
         ];
 
         let content: Element<'_, TreeMapMessage> = if self.scan.is_none() {
-            container(
-                center(text("Click the top left button to start a scan").size(20))
-                    .width(Length::Fill)
-                    .height(Length::Fill),
-            )
-            .style(|_theme: &Theme| {
-                container::Style::default()
-                    .background(Background::Color(Color::from_rgb(0.95, 0.95, 0.95)))
-            })
-            .into()
+            // one click scans a recent directory again
+            let recent = self.existing_roots.iter().map(|path| {
+                button(text(path.to_string_lossy()).size(14))
+                    .style(button::text)
+                    .on_press(TreeMapMessage::FolderSelected(Some(path.clone())))
+                    .into()
+            });
+            let mut start = column![text("Click the top left button to start a scan").size(20)]
+                .spacing(6)
+                .align_x(iced::Alignment::Center);
+            if !self.existing_roots.is_empty() {
+                start = start.push(text("or scan a recent directory again").size(14));
+                start = start.push(column(recent).align_x(iced::Alignment::Center));
+            }
+            container(center(start).width(Length::Fill).height(Length::Fill))
+                .style(|_theme: &Theme| {
+                    container::Style::default().background(Background::Color(Color::from(
+                        self.program.colors.empty_background,
+                    )))
+                })
+                .into()
         } else {
             let canvas_widget = Canvas::new(&self.program)
                 .width(Length::Fill)
@@ -474,12 +1604,25 @@ impl TreeMapApp {
 
             let mut tooltip_text = String::from("");
             if let Some(id) = self.program.active_node {
-                if let Some(gnode) = self.program.gui_nodes.iter().find(|x| id == x.node_id) {
-                    tooltip_text = gnode.label.clone();
+                if let Some(label) = self.program.label_of(id) {
+                    tooltip_text = label;
+                }
+                match self.color_mode {
+                    ColorMode::Age => tooltip_text.push_str(&self.age_tooltip(id)),
+                    ColorMode::Owner => tooltip_text.push_str(&self.owner_tooltip(id)),
+                    ColorMode::Git => tooltip_text.push_str(&self.git_tooltip(id)),
+                    ColorMode::Depth => {}
                 }
+            } else if let Some(gnode) = self
+                .program
+                .hovered_small_items
+                .and_then(|id| self.program.small_items_of(id))
+            {
+                tooltip_text = gnode.label.clone();
+                tooltip_text.push_str(&self.small_items_tooltip(&gnode.small_items));
             }
 
-            if self.program.context_menu.is_none() && self.node_pending_trash.is_none() {
+            if self.program.context_menu.is_none() && self.nodes_pending_trash.is_none() {
                 container(tooltip(
                     canvas_widget,
                     container(text(tooltip_text).style(|_| text::Style {
@@ -506,14 +1649,86 @@ impl TreeMapApp {
             }
         };
 
-        let main_layout = column![header, content];
+        let panel_body = match self.panel {
+            Some(panel::Panel::List | panel::Panel::SmallItems) => Some(panel::list(
+                &self.list_rows,
+                self.list_sort,
+                self.size_mode,
+                self.program.active_node,
+                self.program.selected_node,
+            )),
+            Some(panel::Panel::Details) => Some(panel::details(self.node_details())),
+            Some(panel::Panel::Owners) => Some(panel::owners(
+                &self.owner_summary,
+                self.owner_filter,
+                self.settings.vision,
+            )),
+            Some(panel::Panel::Appearance) => Some(panel::appearance(&self.settings)),
+            Some(panel::Panel::Largest) => Some(panel::largest(
+                &self.largest_files,
+                self.scan
+                    .as_ref()
+                    .is_some_and(|s| s.complete.load(Ordering::SeqCst)),
+                self.program.active_node,
+            )),
+            Some(panel::Panel::Empty) => Some(panel::empty(
+                &self.empty,
+                self.scan
+                    .as_ref()
+                    .is_some_and(|s| s.complete.load(Ordering::SeqCst)),
+                self.program.active_node,
+            )),
+            Some(panel::Panel::Extensions) => Some(panel::extensions(
+                &self.extension_stats,
+                self.extension_sort,
+                self.selected_extension.as_deref(),
+            )),
+            Some(panel::Panel::Old) => Some(panel::old(
+                &self.old_files,
+                self.old_query,
+                self.old_filter,
+                self.program.active_node,
+            )),
+            Some(panel::Panel::Reclaimable) => Some(panel::reclaimable(
+                &self.reclaimable,
+                &self.reclaim_selected,
+                self.program.active_node,
+            )),
+            Some(panel::Panel::Duplicates) => Some(panel::duplicates(
+                self.duplicates.as_deref(),
+                self.duplicates_searching,
+                self.scan
+                    .as_ref()
+                    .is_some_and(|s| s.complete.load(Ordering::SeqCst)),
+                self.program.active_node,
+            )),
+            None => None,
+        };
+        let content: Element<'_, TreeMapMessage> = match (self.panel, panel_body) {
+            (Some(panel), Some(body)) => row![
+                content,
+                panel::resize_handle(),
+                panel::frame(panel.to_string(), self.panel_width, body)
+            ]
+            .into(),
+            _ => content,
+        };
+
+        let main_layout = column![header, self.legend(), content];
 
-        if let Some(gnode) = &self.node_pending_trash {
-            let modal = container(
-                column![
-                    text("Are you sure you want to trash this item?")
-                        .font(iced::Font::DEFAULT.weight(iced::font::Weight::Bold)),
-                    text(&gnode.label),
+        let modal: Option<(Element<'_, TreeMapMessage>, f32)> =
+            if let Some((node_ids, label)) = &self.nodes_pending_trash {
+                let question = if node_ids.len() == 1 {
+                    String::from("Are you sure you want to trash this item?")
+                } else {
+                    format!(
+                        "Are you sure you want to trash these {} items?",
+                        count_display(node_ids.len() as u64)
+                    )
+                };
+                let body = column![
+                    text(question).font(iced::Font::DEFAULT.weight(iced::font::Weight::Bold)),
+                    text(label),
                     row![
                         button("Cancel")
                             .style(button::secondary)
@@ -527,21 +1742,54 @@ impl TreeMapApp {
                     .spacing(20)
                 ]
                 .spacing(15)
-                .align_x(iced::Alignment::Center),
-            )
-            .width(320)
-            .padding(20)
-            .style(|theme: &Theme| {
-                let palette = theme.palette();
-                container::Style::default()
-                    .background(Background::Color(palette.background.weakest.color))
-                    .border(
-                        Border::default()
-                            .color(palette.background.strong.color)
-                            .width(1.0)
-                            .rounded(5.0),
-                    )
-            });
+                .align_x(iced::Alignment::Center);
+                Some((body.into(), 320.0))
+            } else if !self.trash_failures.is_empty() {
+                let failures = self.trash_failures.iter().fold(
+                    column![].spacing(5),
+                    |failures, (path, error)| {
+                        failures.push(text(format!("{}: {}", path, error)).size(12.0))
+                    },
+                );
+                let body = column![
+                    text(format!(
+                        "{} couldn't be trashed",
+                        match self.trash_failures.len() {
+                            1 => String::from("1 item"),
+                            n => format!("{} items", count_display(n as u64)),
+                        }
+                    ))
+                    .font(iced::Font::DEFAULT.weight(iced::font::Weight::Bold)),
+                    container(scrollable(failures).width(Length::Fill)).max_height(300),
+                    button("OK")
+                        .style(button::primary)
+                        .padding(5)
+                        .on_press(TreeMapMessage::TrashFailuresDismissed),
+                ]
+                .spacing(15)
+                .align_x(iced::Alignment::Center);
+                Some((body.into(), 480.0))
+            } else {
+                self.settings_draft
+                    .as_ref()
+                    .map(|draft| (settings_dialog::view(draft), 480.0))
+            };
+
+        if let Some((body, width)) = modal {
+            let modal = container(body)
+                .width(width)
+                .padding(20)
+                .style(|theme: &Theme| {
+                    let palette = theme.palette();
+                    container::Style::default()
+                        .background(Background::Color(palette.background.weakest.color))
+                        .border(
+                            Border::default()
+                                .color(palette.background.strong.color)
+                                .width(1.0)
+                                .rounded(5.0),
+                        )
+                });
 
             let overlay = container(center(modal))
                 .width(Length::Fill)
@@ -557,27 +1805,492 @@ impl TreeMapApp {
         }
     }
 
+    // the owner summary and the panels that go through the whole shown tree,
+    // with the nodes they mark
+    fn update_reports(&mut self, tree: &Tree, shown_root: NodeID, now: u64) {
+        if self.panel == Some(panel::Panel::Owners) || self.color_mode == ColorMode::Owner {
+            self.owner_summary = owner_summary(tree, shown_root);
+        }
+        self.program.marked_nodes.clear();
+        if self.panel == Some(panel::Panel::Empty) {
+            self.empty = find_empty(tree, shown_root);
+        }
+        if self.panel == Some(panel::Panel::Extensions) {
+            self.extension_stats = extension_stats(tree, shown_root);
+            sort_stats(
+                &mut self.extension_stats,
+                self.extension_sort.0,
+                self.extension_sort.1,
+            );
+            if let Some(extension) = &self.selected_extension {
+                self.program.marked_nodes = files_with_extension(tree, shown_root, extension)
+                    .into_iter()
+                    .collect();
+            }
+        }
+        if self.panel == Some(panel::Panel::Old) {
+            self.old_files = find_old_files(tree, shown_root, self.old_query, now);
+        }
+        if self.panel == Some(panel::Panel::Reclaimable) {
+            self.reclaimable = find_reclaimable(tree, shown_root, &self.settings.reclaim_rules);
+            self.program.marked_nodes = self
+                .reclaimable
+                .iter()
+                .flat_map(|m| m.dirs.iter().map(|d| d.node_id))
+                .collect();
+        }
+    }
+
+    fn age_tooltip(&self, node_id: NodeID) -> String {
+        let Some(scan) = &self.scan else {
+            return String::new();
+        };
+        let Ok(tree) = scan.tree_mutex.lock() else {
+            return String::new();
+        };
+        let node = tree.get_elem(node_id);
+        let now = now_secs();
+        let ago = |t: Option<u64>| {
+            t.map(|t| age_display(now.saturating_sub(t)))
+                .unwrap_or_else(|| String::from("unknown"))
+        };
+        if node.is_file {
+            format!(
+                "\nmodified: {}\naccessed: {}",
+                ago(node.mtime),
+                ago(node.atime)
+            )
+        } else {
+            format!(
+                "\nnewest: {}\noldest: {}",
+                ago(node.newest_mtime),
+                ago(node.oldest_mtime)
+            )
+        }
+    }
+
+    // show the contents of a directory, files can't be focused on
+    fn focus_on_node(&mut self, node_id: NodeID) -> Task<TreeMapMessage> {
+        // don't focus on the same node again
+        if self.nodes_pending_trash.is_some()
+            || *self.shown_root_id_history.last().unwrap_or(&0_usize) == node_id
+        {
+            return Task::none();
+        }
+        if let Some(scan) = &self.scan
+            && let Ok(tree) = scan.tree_mutex.lock()
+        {
+            let node = tree.get_elem(node_id);
+            if !node.is_file {
+                self.program.start_zoom_in(node.id);
+                self.shown_root_forward.clear();
+                self.shown_root_id_history.push(node.id);
+                self.shown_root_path_history
+                    .push(node.path.to_string_lossy().into());
+                return Task::done(TreeMapMessage::RecalculateRects);
+            }
+        }
+        Task::none()
+    }
+
+    // goes straight to an ancestor of the shown root, or to any directory below the scanned one
+    // history entries that aren't on the way there are dropped
+    fn jump_to_node(&mut self, node_id: NodeID) -> Task<TreeMapMessage> {
+        if self.nodes_pending_trash.is_some() {
+            return Task::none();
+        }
+        let Some(scan) = &self.scan else {
+            return Task::none();
+        };
+        let Ok(tree) = scan.tree_mutex.lock() else {
+            return Task::none();
+        };
+        let lineage = tree.lineage(node_id);
+        self.shown_root_forward.clear();
+        self.zoom_out_from = self.shown_root_id_history.last().copied();
+        let kept = self
+            .shown_root_id_history
+            .iter()
+            .take_while(|id| lineage.contains(id))
+            .count();
+        self.shown_root_id_history.truncate(kept);
+        self.shown_root_path_history.truncate(kept + 1); // the root path stays
+        if node_id != 0 && self.shown_root_id_history.last() != Some(&node_id) {
+            self.shown_root_id_history.push(node_id);
+            self.shown_root_path_history
+                .push(tree.get_elem(node_id).path.to_string_lossy().into());
+        }
+        Task::done(TreeMapMessage::RecalculateRects)
+    }
+
+    fn move_list_selection(&mut self, step: isize) -> Task<TreeMapMessage> {
+        if self.list_rows.is_empty() {
+            return Task::none();
+        }
+        let last = self.list_rows.len() - 1;
+        let index = match self
+            .program
+            .selected_node
+            .and_then(|id| self.list_rows.iter().position(|r| r.node_id == id))
+        {
+            Some(index) => index.saturating_add_signed(step).min(last),
+            None if step < 0 => last,
+            None => 0,
+        };
+        self.program.selected_node = Some(self.list_rows[index].node_id);
+        self.program.rects_cache.clear();
+        // scrolling proportionally keeps the row within the visible part
+        iced::widget::operation::snap_to(
+            panel::SCROLL_ID,
+            scrollable::RelativeOffset {
+                x: None,
+                y: Some(index as f32 / last.max(1) as f32),
+            },
+        )
+    }
+
+    // moves to the nearest sibling in the given direction, off-axis distance counts double
+    // without a selection, the first child of the shown root is picked
+    fn move_selection(&mut self, direction: Direction) {
+        let Some(scan) = &self.scan else {
+            return;
+        };
+        let Ok(tree) = scan.tree_mutex.lock() else {
+            return;
+        };
+        let shown_root = *self.shown_root_id_history.last().unwrap_or(&0_usize);
+        let candidates: Vec<(NodeID, Point)> = self
+            .program
+            .node_centers()
+            .into_iter()
+            .filter(|(id, _)| tree.get_elem(*id).parent == Some(shown_root))
+            .collect();
+        let current = self
+            .program
+            .selected_node
+            .and_then(|id| candidates.iter().find(|(c, _)| *c == id));
+        let next = match current {
+            None => candidates.first(),
+            Some((current_id, from)) => candidates
+                .iter()
+                .filter(|(id, _)| id != current_id)
+                .filter_map(|(id, to)| {
+                    let (dx, dy) = (to.x - from.x, to.y - from.y);
+                    let (along, across) = match direction {
+                        Direction::Up => (-dy, dx),
+                        Direction::Down => (dy, dx),
+                        Direction::Left => (-dx, dy),
+                        Direction::Right => (dx, dy),
+                    };
+                    (along > 0.5).then_some((id, along + 2.0 * across.abs()))
+                })
+                .min_by(|a, b| a.1.total_cmp(&b.1))
+                .and_then(|(id, _)| candidates.iter().find(|(c, _)| c == id)),
+        };
+        if let Some((id, _)) = next {
+            self.program.selected_node = Some(*id);
+            self.program.rects_cache.clear();
+        }
+    }
+
+    fn current_cushion_key(&self) -> CushionKey {
+        (
+            *self.shown_root_id_history.last().unwrap_or(&0_usize),
+            self.program.bounds.width as u32,
+            self.program.bounds.height as u32,
+            [
+                self.program.pan_zoom.scale.to_bits(),
+                self.program.pan_zoom.x.to_bits(),
+                self.program.pan_zoom.y.to_bits(),
+            ],
+        )
+    }
+
+    // rendering takes a while, so only one runs at a time in the background
+    // layout changes in the meantime are picked up by another run when it's done
+    fn render_cushion(&mut self) -> Task<TreeMapMessage> {
+        if !self.settings.cushions
+            || self.program.view != ViewMode::Treemap
+            || self.program.gui_nodes.is_empty()
+        {
+            return Task::none();
+        }
+        if self.cushion_rendering {
+            self.cushion_outdated = true;
+            return Task::none();
+        }
+        self.cushion_rendering = true;
+        let key = self.current_cushion_key();
+        let gui_nodes = self.program.gui_nodes.clone();
+        let background = self.program.colors.border;
+        Task::perform(
+            async move {
+                let pixels = cushion::render(&gui_nodes, key.1, key.2, background);
+                image::Handle::from_rgba(key.1, key.2, pixels)
+            },
+            move |handle| TreeMapMessage::CushionRendered(key, handle),
+        )
+    }
+
+    fn save_state(&self) {
+        if let Err(e) = self.state.save() {
+            eprintln!("Can't save state: {}", e);
+        }
+    }
+
+    // a file that had errors is kept as a backup the first time it's saved over
+    fn save_settings(&mut self) {
+        if !self.settings_errors.is_empty() {
+            match Settings::backup() {
+                Ok(backup) => eprintln!(
+                    "The settings file with errors is kept as {}",
+                    backup.display()
+                ),
+                Err(e) => {
+                    eprintln!("Can't back up the settings file, not saving over it: {}", e);
+                    return;
+                }
+            }
+            self.settings_errors.clear();
+        }
+        if let Err(e) = self.settings.save() {
+            eprintln!("Can't save settings: {}", e);
+        }
+    }
+
+    // of the hovered node, or of the selection when nothing is hovered
+    fn node_details(&self) -> Option<NodeDetails> {
+        let id = self.program.active_node.or(self.program.selected_node)?;
+        let scan = self.scan.as_ref()?;
+        let tree = scan.tree_mutex.lock().ok()?;
+        Some(details::node_details(&tree, id))
+    }
+
+    // the largest of the grouped items
+    fn small_items_tooltip(&self, ids: &[NodeID]) -> String {
+        let Some(scan) = &self.scan else {
+            return String::new();
+        };
+        let Ok(tree) = scan.tree_mutex.lock() else {
+            return String::new();
+        };
+        let mut nodes: Vec<_> = ids.iter().map(|id| tree.get_elem(*id)).collect();
+        nodes.sort_by_key(|n| std::cmp::Reverse(self.size_mode.of(n)));
+        let mut breakdown = String::new();
+        for node in nodes.iter().take(config::SMALL_ITEMS_BREAKDOWN) {
+            breakdown.push_str(&format!(
+                "\n{} ({})",
+                node.name,
+                self.size_mode.display(self.size_mode.of(node))
+            ));
+        }
+        if nodes.len() > config::SMALL_ITEMS_BREAKDOWN {
+            breakdown.push_str(&format!(
+                "\n… and {} more, click to list them all",
+                count_display((nodes.len() - config::SMALL_ITEMS_BREAKDOWN) as u64)
+            ));
+        }
+        breakdown
+    }
+
+    fn shows_small_item(&self, node_id: NodeID) -> bool {
+        self.panel == Some(panel::Panel::SmallItems)
+            && self
+                .small_items
+                .as_ref()
+                .is_some_and(|(_, ids)| ids.contains(&node_id))
+    }
+
+    fn node_label(&self, node_id: NodeID) -> Option<String> {
+        let scan = self.scan.as_ref()?;
+        let tree = scan.tree_mutex.lock().ok()?;
+        let node = tree.get_elem(node_id);
+        Some(format!(
+            "{} ({})",
+            node.path.to_string_lossy(),
+            bytes_display(node.size)
+        ))
+    }
+
+    fn git_tooltip(&self, node_id: NodeID) -> String {
+        let Some(scan) = &self.scan else {
+            return String::new();
+        };
+        let Ok(tree) = scan.tree_mutex.lock() else {
+            return String::new();
+        };
+        let node = tree.get_elem(node_id);
+        let bytes = node.git_bytes;
+        match node.git {
+            Some(status) => format!("\ngit: {}", status),
+            None if bytes.total() > 0 => format!(
+                "\ntracked: {}\nuntracked: {}\nignored: {}",
+                bytes_display(bytes.tracked),
+                bytes_display(bytes.untracked),
+                bytes_display(bytes.ignored)
+            ),
+            None if scan.complete.load(Ordering::SeqCst) => String::from("\nnot in a repository"),
+            None => String::from("\nknown once the scan is complete"),
+        }
+    }
+
+    fn owner_tooltip(&self, node_id: NodeID) -> String {
+        let Some(scan) = &self.scan else {
+            return String::new();
+        };
+        let Ok(tree) = scan.tree_mutex.lock() else {
+            return String::new();
+        };
+        let node = tree.get_elem(node_id);
+        format!("\nowner: {}:{}", user_name(node.uid), group_name(node.gid))
+    }
+
+    // a button for every directory from the scanned one to the shown one
+    fn breadcrumbs(&self) -> Element<'_, TreeMapMessage> {
+        let crumbs: Vec<(NodeID, String)> = match &self.scan {
+            Some(scan) => match scan.tree_mutex.lock() {
+                Ok(tree) => {
+                    let shown_root = *self.shown_root_id_history.last().unwrap_or(&0_usize);
+                    tree.lineage(shown_root)
+                        .into_iter()
+                        .map(|id| (id, tree.get_elem(id).name.clone()))
+                        .collect()
+                }
+                Err(_) => vec![],
+            },
+            None => vec![],
+        };
+        if crumbs.is_empty() {
+            return text("SpaceMan")
+                .size(15.0)
+                .font(iced::Font::DEFAULT.weight(iced::font::Weight::Bold))
+                .into();
+        }
+
+        let last = crumbs.len() - 1;
+        let mut segments = row![].align_y(iced::Alignment::Center);
+        for (i, (id, name)) in crumbs.into_iter().enumerate() {
+            if i > 0 {
+                segments = segments.push(text("›").size(15.0));
+            }
+            let label = text(name).size(15.0);
+            let label = if i == last {
+                label.font(iced::Font::DEFAULT.weight(iced::font::Weight::Bold))
+            } else {
+                label
+            };
+            segments = segments.push(
+                mouse_area(
+                    button(label)
+                        .style(button::text)
+                        .padding([2, 4])
+                        .on_press(TreeMapMessage::BreadcrumbClicked(id)),
+                )
+                .on_middle_press(TreeMapMessage::ExecuteAction(String::from("Show"), id)),
+            );
+        }
+        scrollable(segments)
+            .direction(scrollable::Direction::Horizontal(
+                scrollable::Scrollbar::new().width(2).scroller_width(2),
+            ))
+            .into()
+    }
+
+    fn legend(&self) -> Element<'_, TreeMapMessage> {
+        let entries = if self.color_mode == ColorMode::Owner {
+            // the largest owners, the rest are listed in the owners panel
+            self.owner_summary
+                .iter()
+                .take(8)
+                .map(|owner| {
+                    (
+                        owner_color(owner.uid, self.settings.vision),
+                        owner.name.clone(),
+                    )
+                })
+                .collect()
+        } else {
+            Colorizer::new(self.color_mode, self.program.colors, self.settings.vision).legend()
+        };
+        if entries.is_empty() {
+            return row![].into();
+        }
+        let swatches = entries.into_iter().map(|(color, label)| {
+            row![panel::swatch(Color::from(color)), text(label).size(13.0),]
+                .spacing(4)
+                .align_y(iced::Alignment::Center)
+                .into()
+        });
+        container(row(swatches).spacing(12))
+            .width(Length::Fill)
+            .padding([2, 6])
+            .into()
+    }
+
     fn theme(&self) -> Theme {
-        Theme::TokyoNightLight
+        self.program.colors.iced.clone()
     }
 
     fn subscription(&self) -> iced::Subscription<TreeMapMessage> {
-        if self.scan.is_some() {
-            iced::time::every(config::UPDATE_PERIOD).map(|_| TreeMapMessage::CheckForScanUpdates)
+        let scan_updates = if self.scan.is_some() {
+            iced::time::every(config::tuning().update_period())
+                .map(|_| TreeMapMessage::CheckForScanUpdates)
         } else {
             iced::Subscription::none()
-        }
+        };
+        // the cursor leaves the handle while dragging, so follow it through the whole window
+        let panel_resize = if self.panel_resize_edge.is_some() {
+            iced::event::listen_with(|event, _status, _window| match event {
+                iced::Event::Mouse(mouse::Event::CursorMoved { position }) => {
+                    Some(TreeMapMessage::PanelResized(position.x))
+                }
+                iced::Event::Mouse(mouse::Event::ButtonReleased(mouse::Button::Left)) => {
+                    Some(TreeMapMessage::PanelResizeEnded)
+                }
+                _ => None,
+            })
+        } else {
+            iced::Subscription::none()
+        };
+        let animation = if self.program.zoom.is_some() {
+            iced::window::frames().map(TreeMapMessage::AnimationTick)
+        } else {
+            iced::Subscription::none()
+        };
+        let window_events = iced::event::listen_with(|event, _status, id| match event {
+            iced::Event::Window(iced::window::Event::Resized(size)) => {
+                Some(TreeMapMessage::WindowResized(size))
+            }
+            iced::Event::Window(iced::window::Event::Moved(position)) => {
+                Some(TreeMapMessage::WindowMoved(position))
+            }
+            iced::Event::Window(iced::window::Event::CloseRequested) => {
+                Some(TreeMapMessage::WindowCloseRequested(id))
+            }
+            _ => None,
+        });
+        iced::Subscription::batch([scan_updates, panel_resize, animation, window_events])
     }
 }
 
 pub fn init(start_with_scan: Option<PathBuf>) -> iced::Result {
-    iced::application(
-        move || TreeMapApp::new(start_with_scan.clone()),
+    let state = AppState::load();
+    let window = state.window;
+    let mut app = iced::application(
+        move || TreeMapApp::new(start_with_scan.clone(), state.clone()),
         TreeMapApp::update,
         TreeMapApp::view,
     )
     .subscription(TreeMapApp::subscription)
     .title(TreeMapApp::title)
     .theme(TreeMapApp::theme)
-    .run()
+    // the state is saved before closing
+    .exit_on_close_request(false);
+    if let Some(window) = window {
+        app = app.window_size(Size::new(window.width, window.height));
+        if let (Some(x), Some(y)) = (window.x, window.y) {
+            app = app.position(iced::window::Position::Specific(Point::new(x, y)));
+        }
+    }
+    app.run()
 }
@@ -0,0 +1,803 @@
+use super::TreeMapMessage;
+use crate::details::NodeDetails;
+use crate::duplicates::DuplicateGroup;
+use crate::empty::EmptyReport;
+use crate::extensions::{ExtensionColumn, ExtensionStats};
+use crate::listing::{ListRow, SortColumn};
+use crate::node_color::{Vision, now_secs, owner_color};
+use crate::old::{DAY, OldFile, OldQuery};
+use crate::owners::OwnerSummary;
+use crate::reclaimable::RuleMatches;
+use crate::settings::Settings;
+use crate::sizes::SizeMode;
+use crate::theme::AppTheme;
+use crate::types::NodeID;
+use crate::utils::{age_display, bytes_display, count_display, mode_display};
+use iced::widget::{
+    button, checkbox, column, container, mouse_area, pick_list, row, scrollable, slider, text,
+};
+use iced::{Background, Border, Color, Element, Length, Theme, mouse};
+use std::collections::HashSet;
+use std::fmt;
+
+pub const PANEL_WIDTH: f32 = 320.0;
+pub const MIN_PANEL_WIDTH: f32 = 240.0;
+pub const MAX_PANEL_WIDTH: f32 = 900.0;
+pub const RESIZE_HANDLE_WIDTH: f32 = 4.0;
+// more groups are left out of the duplicates panel, the largest come first
+const MAX_DUPLICATE_GROUPS: usize = 200;
+// the same for the directories listed under each reclaimable rule
+const MAX_RECLAIMABLE_DIRS: usize = 50;
+// and for each list of the empty items panel
+const MAX_EMPTY_ITEMS: usize = 500;
+// and for the old files panel
+const MAX_OLD_FILES: usize = 500;
+// the sizes the old files slider steps through
+pub const OLD_SIZE_STEPS: [u64; 7] = [
+    0,
+    1024 * 1024,
+    10 * 1024 * 1024,
+    100 * 1024 * 1024,
+    1024 * 1024 * 1024,
+    10 * 1024 * 1024 * 1024,
+    100 * 1024 * 1024 * 1024,
+];
+// the longest time the age slider goes to, in months of 30 days
+pub const OLD_MAX_MONTHS: u8 = 60;
+// the scrollable holding the panel body, to keep the list selection in view
+pub const SCROLL_ID: &str = "panel-body";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Panel {
+    List,
+    Details,
+    Owners,
+    Appearance,
+    Duplicates,
+    Reclaimable,
+    Empty,
+    Largest,
+    Old,
+    Extensions,
+    // the children grouped into a block of smaller items, opened by clicking the block
+    SmallItems,
+}
+
+impl Panel {
+    pub const ALL: [Panel; 10] = [
+        Panel::List,
+        Panel::Details,
+        Panel::Largest,
+        Panel::Old,
+        Panel::Extensions,
+        Panel::Owners,
+        Panel::Duplicates,
+        Panel::Reclaimable,
+        Panel::Empty,
+        Panel::Appearance,
+    ];
+}
+
+impl fmt::Display for Panel {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Panel::List => write!(f, "List"),
+            Panel::Details => write!(f, "Details"),
+            Panel::Owners => write!(f, "Owners"),
+            Panel::Appearance => write!(f, "Appearance"),
+            Panel::Duplicates => write!(f, "Duplicates"),
+            Panel::Reclaimable => write!(f, "Reclaimable"),
+            Panel::Empty => write!(f, "Empty items"),
+            Panel::Largest => write!(f, "Largest files"),
+            Panel::Old => write!(f, "Old files"),
+            Panel::Extensions => write!(f, "Extensions"),
+            Panel::SmallItems => write!(f, "Smaller items"),
+        }
+    }
+}
+
+// title bar with a close button, and a scrollable body
+pub fn frame<'a>(
+    title: String,
+    width: f32,
+    body: impl Into<Element<'a, TreeMapMessage>>,
+) -> Element<'a, TreeMapMessage> {
+    container(
+        column![
+            row![
+                text(title)
+                    .size(15.0)
+                    .font(iced::Font::DEFAULT.weight(iced::font::Weight::Bold))
+                    .width(Length::Fill),
+                button(text("×").size(14.0))
+                    .style(button::text)
+                    .padding([0, 6])
+                    .on_press(TreeMapMessage::PanelClosed),
+            ]
+            .align_y(iced::Alignment::Center),
+            scrollable(body).id(SCROLL_ID).height(Length::Fill),
+        ]
+        .spacing(6),
+    )
+    .width(width)
+    .height(Length::Fill)
+    .padding(6)
+    .style(|theme: &Theme| {
+        let palette = theme.palette();
+        container::Style::default()
+            .background(Background::Color(palette.background.weakest.color))
+            .border(
+                Border::default()
+                    .color(palette.background.strong.color)
+                    .width(1.0),
+            )
+    })
+    .into()
+}
+
+// dragged to resize the panel, sits on its left edge
+pub fn resize_handle<'a>() -> Element<'a, TreeMapMessage> {
+    mouse_area(
+        container(text(""))
+            .width(RESIZE_HANDLE_WIDTH)
+            .height(Length::Fill),
+    )
+    .interaction(mouse::Interaction::ResizingHorizontally)
+    .on_press(TreeMapMessage::PanelResizeStarted)
+    .into()
+}
+
+pub fn swatch<'a>(color: Color) -> Element<'a, TreeMapMessage> {
+    container(text(""))
+        .width(14)
+        .height(14)
+        .style(move |_theme: &Theme| {
+            container::Style::default()
+                .background(Background::Color(color))
+                .border(Border::default().color(Color::BLACK).width(1.0))
+        })
+        .into()
+}
+
+pub fn owners<'a>(
+    summary: &'a [OwnerSummary],
+    filter: Option<Option<u32>>,
+    vision: Vision,
+) -> Element<'a, TreeMapMessage> {
+    let header = row![
+        text("Owner").width(Length::Fill),
+        text("Size").width(80),
+        text("Files").width(60),
+    ]
+    .padding([0, 4]);
+
+    let rows = summary.iter().map(|owner| {
+        let color = owner_color(owner.uid, vision);
+        let selected = filter == Some(owner.uid);
+        button(
+            row![
+                row![swatch(Color::from(color)), text(&owner.name)]
+                    .spacing(6)
+                    .width(Length::Fill),
+                text(bytes_display(owner.bytes)).width(80),
+                text(count_display(owner.files)).width(60),
+            ]
+            .align_y(iced::Alignment::Center),
+        )
+        .width(Length::Fill)
+        .padding([2, 4])
+        .style(move |theme: &Theme, status| {
+            if selected {
+                button::primary(theme, status)
+            } else {
+                button::text(theme, status)
+            }
+        })
+        .on_press(if selected {
+            TreeMapMessage::OwnerFilterCleared
+        } else {
+            TreeMapMessage::OwnerFilterSelected(owner.uid)
+        })
+        .into()
+    });
+
+    let hint = text(match filter {
+        Some(_) => "Click the owner again to show all files",
+        None => "Click an owner to show only their files",
+    })
+    .size(12.0);
+
+    column![hint, header, column(rows)].spacing(4).into()
+}
+
+pub fn appearance<'a>(settings: &Settings) -> Element<'a, TreeMapMessage> {
+    column![
+        text("Theme"),
+        pick_list(
+            AppTheme::ALL,
+            Some(settings.theme),
+            TreeMapMessage::ThemeSelected
+        )
+        .width(Length::Fill),
+        text("Color vision"),
+        pick_list(
+            Vision::ALL,
+            Some(settings.vision),
+            TreeMapMessage::VisionSelected
+        )
+        .width(Length::Fill),
+        checkbox(settings.cues)
+            .label("Outline cues for files, directories and hover")
+            .on_toggle(TreeMapMessage::CuesToggled),
+        text(
+            "Directories get a solid outline, files a dashed one, and the hovered item a thick one."
+        )
+        .size(12.0),
+        checkbox(settings.cushions)
+            .label("Cushion shading")
+            .on_toggle(TreeMapMessage::CushionsToggled),
+    ]
+    .spacing(6)
+    .into()
+}
+
+pub fn list<'a>(
+    rows: &'a [ListRow],
+    sort: (SortColumn, bool),
+    size_mode: SizeMode,
+    hovered: Option<NodeID>,
+    selected: Option<NodeID>,
+) -> Element<'a, TreeMapMessage> {
+    let (sort_column, descending) = sort;
+    let header_cell = |column: SortColumn| {
+        let arrow = match (column == sort_column, descending) {
+            (false, _) => "",
+            (true, true) => " ▼",
+            (true, false) => " ▲",
+        };
+        button(text(format!("{}{}", column, arrow)).size(13.0))
+            .style(button::text)
+            .padding(0)
+            .on_press(TreeMapMessage::ListSortSelected(column))
+    };
+    let header = row![
+        header_cell(SortColumn::Name).width(Length::Fill),
+        header_cell(SortColumn::Size).width(70),
+        text("%").size(13.0).width(40),
+        header_cell(SortColumn::Items).width(50),
+        header_cell(SortColumn::Modified).width(95),
+    ]
+    .padding([0, 4]);
+
+    let now = now_secs();
+    let rows = rows.iter().map(|item| {
+        let id = item.node_id;
+        let is_hovered = hovered == Some(id);
+        let is_selected = selected == Some(id);
+        let name = if item.is_file {
+            item.name.clone()
+        } else {
+            format!("{}/", item.name)
+        };
+        let modified = item
+            .mtime
+            .map(|t| age_display(now.saturating_sub(t)))
+            .unwrap_or_default();
+        mouse_area(
+            container(
+                row![
+                    text(name).size(13.0).width(Length::Fill),
+                    text(size_mode.display(item.size)).size(13.0).width(70),
+                    text(format!("{:.1}", item.fraction * 100.0))
+                        .size(13.0)
+                        .width(40),
+                    text(item.items.to_string()).size(13.0).width(50),
+                    text(modified).size(13.0).width(95),
+                ]
+                .align_y(iced::Alignment::Center),
+            )
+            .width(Length::Fill)
+            .padding([2, 4])
+            .style(move |theme: &Theme| {
+                let palette = theme.palette();
+                if is_selected {
+                    container::Style::default()
+                        .background(Background::Color(palette.primary.base.color))
+                        .color(palette.primary.base.text)
+                } else if is_hovered {
+                    container::Style::default()
+                        .background(Background::Color(palette.background.strong.color))
+                } else {
+                    container::Style::default()
+                }
+            }),
+        )
+        .on_press(TreeMapMessage::ListRowSelected(id))
+        .on_double_click(TreeMapMessage::ListRowOpened(id))
+        .on_enter(TreeMapMessage::NodeHovered(Some(id)))
+        .on_exit(TreeMapMessage::NodeHovered(None))
+        .into()
+    });
+
+    let hint = text("Up and down move the selection, Enter or a double click opens it").size(12.0);
+
+    column![hint, header, column(rows)].spacing(4).into()
+}
+
+// bytes and counts per file extension, a click marks the files of one in the treemap
+pub fn extensions<'a>(
+    stats: &'a [ExtensionStats],
+    sort: (ExtensionColumn, bool),
+    selected: Option<&str>,
+) -> Element<'a, TreeMapMessage> {
+    let (sort_column, descending) = sort;
+    let header_cell = |column: ExtensionColumn| {
+        let arrow = match (column == sort_column, descending) {
+            (false, _) => "",
+            (true, true) => " ▼",
+            (true, false) => " ▲",
+        };
+        button(text(format!("{}{}", column, arrow)).size(13.0))
+            .style(button::text)
+            .padding(0)
+            .on_press(TreeMapMessage::ExtensionSortSelected(column))
+    };
+    let header = row![
+        header_cell(ExtensionColumn::Extension).width(Length::Fill),
+        header_cell(ExtensionColumn::Bytes).width(70),
+        text("%").size(13.0).width(40),
+        header_cell(ExtensionColumn::Files).width(70),
+    ]
+    .padding([0, 4]);
+
+    let total: u64 = stats.iter().map(|s| s.bytes).sum();
+    let rows = stats.iter().map(|item| {
+        let is_selected = selected == Some(item.extension.as_str());
+        let name = if item.extension.is_empty() {
+            String::from("(no extension)")
+        } else {
+            format!(".{}", item.extension)
+        };
+        let percent = if total == 0 {
+            0.0
+        } else {
+            item.bytes as f64 / total as f64 * 100.0
+        };
+        mouse_area(
+            container(
+                row![
+                    text(name).size(13.0).width(Length::Fill),
+                    text(bytes_display(item.bytes)).size(13.0).width(70),
+                    text(format!("{:.1}", percent)).size(13.0).width(40),
+                    text(count_display(item.count)).size(13.0).width(70),
+                ]
+                .align_y(iced::Alignment::Center),
+            )
+            .width(Length::Fill)
+            .padding([2, 4])
+            .style(move |theme: &Theme| {
+                let palette = theme.palette();
+                if is_selected {
+                    container::Style::default()
+                        .background(Background::Color(palette.primary.base.color))
+                        .color(palette.primary.base.text)
+                } else {
+                    container::Style::default()
+                }
+            }),
+        )
+        .on_press(TreeMapMessage::ExtensionSelected(item.extension.clone()))
+        .into()
+    });
+
+    let hint = text("Click an extension to mark its files in the treemap").size(12.0);
+
+    column![hint, header, column(rows)].spacing(4).into()
+}
+
+pub fn details<'a>(details: Option<NodeDetails>) -> Element<'a, TreeMapMessage> {
+    let Some(details) = details else {
+        return text("Hover over or select an item to see its details")
+            .size(12.0)
+            .into();
+    };
+    let percent = |fraction: f32| format!("{:.1}%", fraction * 100.0);
+    let yes_no = |value: bool| String::from(if value { "yes" } else { "no" });
+
+    let mut fields = vec![
+        ("Name", details.name),
+        ("Path", details.path),
+        ("Size", bytes_display(details.size)),
+        ("On disk", bytes_display(details.disk_size)),
+    ];
+    if let Some(fraction) = details.parent_fraction {
+        fields.push(("Of parent", percent(fraction)));
+    }
+    fields.push(("Of scan", percent(details.root_fraction)));
+    if !details.is_file {
+        fields.push(("Files", details.files.to_string()));
+        fields.push(("Directories", details.dirs.to_string()));
+        if let Some((name, size)) = details.largest_child {
+            fields.push((
+                "Largest item",
+                format!("{} ({})", name, bytes_display(size)),
+            ));
+        }
+    }
+    fields.push((
+        "Modified",
+        details
+            .mtime
+            .map(|t| age_display(now_secs().saturating_sub(t)))
+            .unwrap_or_else(|| String::from("unknown")),
+    ));
+    fields.push(("Owner", details.owner));
+    if let Some(mode) = details.mode {
+        fields.push(("Permissions", mode_display(mode)));
+    }
+    if let Some(status) = details.git {
+        fields.push(("Git", status.to_string()));
+    } else if details.git_bytes.total() > 0 {
+        let bytes = details.git_bytes;
+        let share = |part: u64| {
+            format!(
+                "{} ({})",
+                bytes_display(part),
+                percent(part as f32 / bytes.total() as f32)
+            )
+        };
+        fields.push(("Git tracked", share(bytes.tracked)));
+        fields.push(("Git untracked", share(bytes.untracked)));
+        fields.push(("Git ignored", share(bytes.ignored)));
+    }
+    fields.push(("Mount point", yes_no(details.is_mount_point)));
+    fields.push(("Symlink", yes_no(details.is_symlink)));
+
+    column(fields.into_iter().map(|(label, value)| {
+        row![text(label).size(13.0).width(100), text(value).size(13.0)]
+            .spacing(6)
+            .into()
+    }))
+    .spacing(4)
+    .into()
+}
+
+// a path that can be shown in the file manager or trashed, hovering marks it in the treemap
+fn path_row<'a>(id: NodeID, path: String, hovered: Option<NodeID>) -> Element<'a, TreeMapMessage> {
+    let is_hovered = hovered == Some(id);
+    mouse_area(
+        container(
+            row![
+                text(path).size(12.0).width(Length::Fill),
+                button(text("Show").size(12.0))
+                    .style(button::text)
+                    .padding([0, 4])
+                    .on_press(TreeMapMessage::ExecuteAction(String::from("Show"), id)),
+                button(text("Trash").size(12.0))
+                    .style(button::text)
+                    .padding([0, 4])
+                    .on_press(TreeMapMessage::PromptTrashNode(id)),
+            ]
+            .align_y(iced::Alignment::Center),
+        )
+        .width(Length::Fill)
+        .padding([1, 4])
+        .style(move |theme: &Theme| {
+            if is_hovered {
+                let palette = theme.palette();
+                container::Style::default()
+                    .background(Background::Color(palette.background.strong.color))
+            } else {
+                container::Style::default()
+            }
+        }),
+    )
+    .on_enter(TreeMapMessage::NodeHovered(Some(id)))
+    .on_exit(TreeMapMessage::NodeHovered(None))
+    .into()
+}
+
+// groups is None until a search was made
+pub fn duplicates<'a>(
+    groups: Option<&'a [DuplicateGroup]>,
+    searching: bool,
+    scan_complete: bool,
+    hovered: Option<NodeID>,
+) -> Element<'a, TreeMapMessage> {
+    let find_button = button(text("Find duplicates").size(13.0)).on_press_maybe(
+        (scan_complete && !searching).then_some(TreeMapMessage::DuplicatesSearched),
+    );
+    let status = if searching {
+        String::from("Comparing files, this reads them from the disk…")
+    } else if !scan_complete {
+        String::from("Duplicates can be searched for once the scan is complete")
+    } else {
+        match groups {
+            None => String::from("Files of the same size are compared by their contents"),
+            Some([]) => String::from("No duplicate files found"),
+            Some(groups) => format!(
+                "{} wasted in {} groups of identical files, marked in the treemap",
+                bytes_display(groups.iter().map(|g| g.wasted()).sum()),
+                count_display(groups.len() as u64)
+            ),
+        }
+    };
+    let mut content = column![row![find_button], text(status).size(12.0)].spacing(6);
+
+    let groups = groups.unwrap_or_default();
+    for group in groups.iter().take(MAX_DUPLICATE_GROUPS) {
+        let title = format!(
+            "{} copies of {}, {} wasted",
+            group.nodes.len(),
+            bytes_display(group.size),
+            bytes_display(group.wasted())
+        );
+        let copies = group
+            .nodes
+            .iter()
+            .map(|(id, path)| path_row(*id, path.clone(), hovered));
+        content = content.push(
+            column![
+                text(title)
+                    .size(13.0)
+                    .font(iced::Font::DEFAULT.weight(iced::font::Weight::Bold)),
+                column(copies)
+            ]
+            .spacing(2),
+        );
+    }
+    if groups.len() > MAX_DUPLICATE_GROUPS {
+        content = content.push(
+            text(format!(
+                "… and {} smaller groups",
+                count_display((groups.len() - MAX_DUPLICATE_GROUPS) as u64)
+            ))
+            .size(12.0),
+        );
+    }
+    content.into()
+}
+
+// the rules ticked are trashed together
+pub fn reclaimable<'a>(
+    matches: &'a [RuleMatches],
+    selected: &HashSet<String>,
+    hovered: Option<NodeID>,
+) -> Element<'a, TreeMapMessage> {
+    if matches.is_empty() {
+        return text("No build outputs or caches found in this directory")
+            .size(12.0)
+            .into();
+    }
+    let selected_size: u64 = matches
+        .iter()
+        .filter(|m| selected.contains(&m.name))
+        .map(|m| m.size())
+        .sum();
+    let trash_button =
+        button(text(format!("Trash selected ({})", bytes_display(selected_size))).size(13.0))
+            .style(button::danger)
+            .on_press_maybe((selected_size > 0).then_some(TreeMapMessage::ReclaimSelectedTrashed));
+
+    let mut content = column![
+        text("Directories that can be made again, marked in the treemap. Rules can be added in the settings file.")
+            .size(12.0),
+        row![trash_button]
+    ]
+    .spacing(6);
+    for rule in matches {
+        let name = rule.name.clone();
+        let title = format!(
+            "{}: {} in {} directories",
+            rule.name,
+            bytes_display(rule.size()),
+            count_display(rule.dirs.len() as u64)
+        );
+        content = content.push(
+            checkbox(selected.contains(&rule.name))
+                .label(title)
+                .text_size(13.0)
+                .on_toggle(move |on| TreeMapMessage::ReclaimRuleToggled(name.clone(), on)),
+        );
+        content = content.push(column(
+            rule.dirs
+                .iter()
+                .take(MAX_RECLAIMABLE_DIRS)
+                .map(|dir| path_row(dir.node_id, dir.path.clone(), hovered)),
+        ));
+        if rule.dirs.len() > MAX_RECLAIMABLE_DIRS {
+            content = content.push(
+                text(format!(
+                    "… and {} smaller ones",
+                    count_display((rule.dirs.len() - MAX_RECLAIMABLE_DIRS) as u64)
+                ))
+                .size(12.0),
+            );
+        }
+    }
+    content.into()
+}
+
+// directories still being scanned look empty, so trashing them all waits for the scan
+pub fn empty<'a>(
+    report: &'a EmptyReport,
+    scan_complete: bool,
+    hovered: Option<NodeID>,
+) -> Element<'a, TreeMapMessage> {
+    let total = report.dirs.len() + report.files.len();
+    if total == 0 {
+        return text("No empty directories or zero-byte files in this directory")
+            .size(12.0)
+            .into();
+    }
+    let trash_button =
+        button(text(format!("Trash all {} items", count_display(total as u64))).size(13.0))
+            .style(button::danger)
+            .on_press_maybe(scan_complete.then_some(TreeMapMessage::EmptyItemsTrashed));
+
+    let mut content = column![
+        text("Directories without any files below them, and files of zero bytes").size(12.0),
+        row![trash_button]
+    ]
+    .spacing(6);
+    if !scan_complete {
+        content = content
+            .push(text("Trashing them all is possible once the scan is complete").size(12.0));
+    }
+    for (title, items) in [
+        ("Empty directories", &report.dirs),
+        ("Zero-byte files", &report.files),
+    ] {
+        if items.is_empty() {
+            continue;
+        }
+        content = content.push(
+            text(format!("{} ({})", title, count_display(items.len() as u64)))
+                .size(13.0)
+                .font(iced::Font::DEFAULT.weight(iced::font::Weight::Bold)),
+        );
+        content = content.push(column(
+            items
+                .iter()
+                .take(MAX_EMPTY_ITEMS)
+                .map(|(id, path)| path_row(*id, path.clone(), hovered)),
+        ));
+        if items.len() > MAX_EMPTY_ITEMS {
+            content = content.push(
+                text(format!(
+                    "… and {} more",
+                    count_display((items.len() - MAX_EMPTY_ITEMS) as u64)
+                ))
+                .size(12.0),
+            );
+        }
+    }
+    content.into()
+}
+
+// (node, size, path), largest first
+pub fn largest<'a>(
+    files: &'a [(NodeID, u64, String)],
+    scan_complete: bool,
+    hovered: Option<NodeID>,
+) -> Element<'a, TreeMapMessage> {
+    let hint = text(if scan_complete {
+        "The largest files anywhere in the scan"
+    } else {
+        "The largest files found so far, this fills in as the scan goes"
+    })
+    .size(12.0);
+
+    let rows = files.iter().enumerate().map(|(i, (id, size, path))| {
+        file_row(format!("{}.", i + 1), 30.0, *id, *size, path, hovered)
+    });
+
+    column![hint, column(rows)].spacing(4).into()
+}
+
+// a file with a label before it, its size, a button to show it in the directory it's in and one to trash it
+fn file_row<'a>(
+    label: String,
+    label_width: f32,
+    id: NodeID,
+    size: u64,
+    path: &'a str,
+    hovered: Option<NodeID>,
+) -> Element<'a, TreeMapMessage> {
+    let is_hovered = hovered == Some(id);
+    mouse_area(
+        container(
+            row![
+                text(label).size(12.0).width(label_width),
+                text(bytes_display(size)).size(12.0).width(70),
+                text(path).size(12.0).width(Length::Fill),
+                button(text("Locate").size(12.0))
+                    .style(button::text)
+                    .padding([0, 4])
+                    .on_press(TreeMapMessage::NodeLocated(id)),
+                button(text("Trash").size(12.0))
+                    .style(button::text)
+                    .padding([0, 4])
+                    .on_press(TreeMapMessage::PromptTrashNode(id)),
+            ]
+            .align_y(iced::Alignment::Center),
+        )
+        .width(Length::Fill)
+        .padding([1, 4])
+        .style(move |theme: &Theme| {
+            if is_hovered {
+                let palette = theme.palette();
+                container::Style::default()
+                    .background(Background::Color(palette.background.strong.color))
+            } else {
+                container::Style::default()
+            }
+        }),
+    )
+    .on_enter(TreeMapMessage::NodeHovered(Some(id)))
+    .on_exit(TreeMapMessage::NodeHovered(None))
+    .into()
+}
+
+// files neither modified nor accessed for a while, largest first
+pub fn old<'a>(
+    files: &'a [OldFile],
+    query: OldQuery,
+    filter: bool,
+    hovered: Option<NodeID>,
+) -> Element<'a, TreeMapMessage> {
+    let months = (query.min_age / (30 * DAY)) as u8;
+    let size_step = OLD_SIZE_STEPS
+        .iter()
+        .position(|s| *s >= query.min_size)
+        .unwrap_or(OLD_SIZE_STEPS.len() - 1) as u8;
+    let size_label = if query.min_size == 0 {
+        String::from("Any size")
+    } else {
+        format!("At least {}", bytes_display(query.min_size))
+    };
+    let age_label = if months == 0 {
+        String::from("Any age")
+    } else {
+        format!("Unused for at least {} months", months)
+    };
+    let now = now_secs();
+    let total: u64 = files.iter().map(|f| f.size).sum();
+
+    let mut content = column![
+        text(age_label).size(12.0),
+        slider(0..=OLD_MAX_MONTHS, months, TreeMapMessage::OldAgeChanged),
+        text(size_label).size(12.0),
+        slider(
+            0..=(OLD_SIZE_STEPS.len() - 1) as u8,
+            size_step,
+            TreeMapMessage::OldSizeChanged
+        ),
+        checkbox(filter)
+            .label("Show only these files in the treemap")
+            .text_size(12.0)
+            .on_toggle(TreeMapMessage::OldFilterToggled),
+        text(format!(
+            "{} in {} files",
+            bytes_display(total),
+            count_display(files.len() as u64)
+        ))
+        .size(13.0)
+        .font(iced::Font::DEFAULT.weight(iced::font::Weight::Bold)),
+    ]
+    .spacing(6);
+    content = content.push(column(files.iter().take(MAX_OLD_FILES).map(|f| {
+        let age = age_display(now.saturating_sub(f.last_used));
+        file_row(age, 95.0, f.node_id, f.size, &f.path, hovered)
+    })));
+    if files.len() > MAX_OLD_FILES {
+        content = content.push(
+            text(format!(
+                "… and {} more",
+                count_display((files.len() - MAX_OLD_FILES) as u64)
+            ))
+            .size(12.0),
+        );
+    }
+    content.into()
+}
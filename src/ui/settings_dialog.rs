@@ -0,0 +1,197 @@
+use super::TreeMapMessage;
+use crate::config::Tuning;
+use crate::settings::{Settings, settings_path};
+use iced::widget::{button, checkbox, column, row, text, text_input};
+use iced::{Color, Element, Length};
+use std::str::FromStr;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SettingsField {
+    UpdatePeriod,
+    MaxVisibleDepth,
+    MinBoxSize,
+    Border,
+    TextSize,
+    Excludes,
+}
+
+// the settings file being edited, the values are kept as typed until saved
+#[derive(Debug, Clone)]
+pub struct SettingsDraft {
+    pub update_period: String,
+    pub max_visible_fs_depth: String,
+    pub min_box_size: String,
+    pub border: String,
+    pub text_size: String,
+    // separated by commas
+    pub excludes: String,
+    pub cross_device: bool,
+    // what was wrong with the file when it was loaded, or with the draft when saving
+    pub errors: Vec<String>,
+}
+
+fn parse<T: FromStr>(name: &str, value: &str, errors: &mut Vec<String>) -> Option<T> {
+    let parsed = value.trim().parse().ok();
+    if parsed.is_none() {
+        errors.push(format!("{} must be a number, not \"{}\"", name, value));
+    }
+    parsed
+}
+
+impl SettingsDraft {
+    pub fn new(settings: &Settings, errors: Vec<String>) -> Self {
+        let tuning = settings.tuning;
+        Self {
+            update_period: tuning.update_period.to_string(),
+            max_visible_fs_depth: tuning.max_visible_fs_depth.to_string(),
+            min_box_size: tuning.min_box_size.to_string(),
+            border: tuning.border.to_string(),
+            text_size: tuning.text_size.to_string(),
+            excludes: settings.excludes.join(", "),
+            cross_device: settings.cross_device,
+            errors,
+        }
+    }
+
+    pub fn edit(&mut self, field: SettingsField, value: String) {
+        match field {
+            SettingsField::UpdatePeriod => self.update_period = value,
+            SettingsField::MaxVisibleDepth => self.max_visible_fs_depth = value,
+            SettingsField::MinBoxSize => self.min_box_size = value,
+            SettingsField::Border => self.border = value,
+            SettingsField::TextSize => self.text_size = value,
+            SettingsField::Excludes => self.excludes = value,
+        }
+    }
+
+    // the settings with the draft put in, or everything wrong with it
+    pub fn apply(&self, settings: &Settings) -> Result<Settings, Vec<String>> {
+        let mut errors = vec![];
+        let current = settings.tuning;
+        let mut tuning = Tuning {
+            update_period: parse("update_period", &self.update_period, &mut errors)
+                .unwrap_or(current.update_period),
+            max_visible_fs_depth: parse(
+                "max_visible_fs_depth",
+                &self.max_visible_fs_depth,
+                &mut errors,
+            )
+            .unwrap_or(current.max_visible_fs_depth),
+            min_box_size: parse("min_box_size", &self.min_box_size, &mut errors)
+                .unwrap_or(current.min_box_size),
+            border: parse("border", &self.border, &mut errors).unwrap_or(current.border),
+            text_size: parse("text_size", &self.text_size, &mut errors)
+                .unwrap_or(current.text_size),
+        };
+        errors.extend(tuning.validate());
+        if !errors.is_empty() {
+            return Err(errors);
+        }
+        Ok(Settings {
+            tuning,
+            excludes: self
+                .excludes
+                .split(',')
+                .map(|e| e.trim().to_string())
+                .filter(|e| !e.is_empty())
+                .collect(),
+            cross_device: self.cross_device,
+            ..settings.clone()
+        })
+    }
+}
+
+fn field<'a>(
+    label: &'a str,
+    hint: &'a str,
+    value: &'a str,
+    field: SettingsField,
+) -> Element<'a, TreeMapMessage> {
+    row![
+        column![text(label).size(13.0), text(hint).size(11.0)].width(Length::Fill),
+        text_input("", value)
+            .size(13.0)
+            .width(160)
+            .on_input(move |v| TreeMapMessage::SettingsEdited(field, v)),
+    ]
+    .spacing(10)
+    .align_y(iced::Alignment::Center)
+    .into()
+}
+
+pub fn view(draft: &SettingsDraft) -> Element<'_, TreeMapMessage> {
+    let path = settings_path()
+        .map(|p| p.display().to_string())
+        .unwrap_or_else(|| String::from("no config directory, settings can't be saved"));
+
+    let mut content = column![
+        text("Settings").font(iced::Font::DEFAULT.weight(iced::font::Weight::Bold)),
+        text(path).size(11.0),
+        field(
+            "Update period",
+            "milliseconds between treemap updates while scanning, 10 to 2000",
+            &draft.update_period,
+            SettingsField::UpdatePeriod,
+        ),
+        field(
+            "Depth",
+            "the most levels of directories drawn, 1 to 64",
+            &draft.max_visible_fs_depth,
+            SettingsField::MaxVisibleDepth,
+        ),
+        field(
+            "Smallest box",
+            "in pixels, smaller items are grouped together, 4 to 200",
+            &draft.min_box_size,
+            SettingsField::MinBoxSize,
+        ),
+        field(
+            "Border",
+            "in pixels around each box, 0 to 10",
+            &draft.border,
+            SettingsField::Border,
+        ),
+        field(
+            "Text size",
+            "of the labels, 6 to 48",
+            &draft.text_size,
+            SettingsField::TextSize,
+        ),
+        field(
+            "Excludes",
+            "names skipped while scanning, like node_modules, *.iso",
+            &draft.excludes,
+            SettingsField::Excludes,
+        ),
+        checkbox(draft.cross_device)
+            .label("Scan into other file systems mounted inside")
+            .text_size(13.0)
+            .on_toggle(TreeMapMessage::SettingsCrossDeviceToggled),
+        text("Excludes and file systems apply from the next scan").size(11.0),
+    ]
+    .spacing(10);
+
+    for error in draft.errors.iter() {
+        content = content.push(
+            text(error.as_str())
+                .size(12.0)
+                .color(Color::from_rgb(0.8, 0.1, 0.1)),
+        );
+    }
+
+    content
+        .push(
+            row![
+                button("Cancel")
+                    .style(button::secondary)
+                    .padding(5)
+                    .on_press(TreeMapMessage::SettingsCancelled),
+                button("Save")
+                    .style(button::primary)
+                    .padding(5)
+                    .on_press(TreeMapMessage::SettingsSaved),
+            ]
+            .spacing(20),
+        )
+        .into()
+}
@@ -0,0 +1,171 @@
+use crate::config::{self, SUNBURST_RINGS};
+use crate::node_color::label_color;
+use crate::sunburst::SunburstNode;
+use crate::theme::ThemeColors;
+use crate::types::NodeID;
+use iced::widget::canvas::{self, Frame, Path};
+use iced::widget::text;
+use iced::{Color, Pixels, Point, Size};
+use std::f32::consts::{FRAC_PI_2, TAU};
+
+// the rings and the center disc share the radius equally
+fn ring_width(size: Size) -> f32 {
+    size.width.min(size.height) / 2.0 / (SUNBURST_RINGS + 1) as f32
+}
+
+fn center(size: Size) -> Point {
+    Point::new(size.width / 2.0, size.height / 2.0)
+}
+
+// our angles start at the top, canvas angles at the positive x axis
+fn point_at(center: Point, radius: f32, angle: f32) -> Point {
+    Point::new(
+        center.x + radius * (angle - FRAC_PI_2).cos(),
+        center.y + radius * (angle - FRAC_PI_2).sin(),
+    )
+}
+
+pub fn locate_node(nodes: &[SunburstNode], size: Size, point: Point) -> Option<NodeID> {
+    let center = center(size);
+    let (dx, dy) = (point.x - center.x, point.y - center.y);
+    let ring = ((dx * dx + dy * dy).sqrt() / ring_width(size)) as usize;
+    let angle = (dy.atan2(dx) + FRAC_PI_2).rem_euclid(TAU);
+    nodes
+        .iter()
+        .find(|node| node.contains(ring, angle))
+        .map(|node| node.node_id)
+}
+
+// middle of the segment, where keyboard navigation measures distances from
+pub fn segment_center(node: &SunburstNode, size: Size) -> Point {
+    if node.ring == 0 {
+        return center(size);
+    }
+    let radius = (node.ring as f32 + 0.5) * ring_width(size);
+    point_at(
+        center(size),
+        radius,
+        (node.start_angle + node.end_angle) / 2.0,
+    )
+}
+
+fn segment_path(node: &SunburstNode, center: Point, ring_width: f32) -> Path {
+    if node.ring == 0 {
+        return Path::circle(center, ring_width);
+    }
+    let inner = node.ring as f32 * ring_width;
+    let outer = inner + ring_width;
+    // approximate the arcs with short lines, both directions are needed
+    let steps = ((node.end_angle - node.start_angle) / 0.02).ceil().max(1.0) as usize;
+    let step = (node.end_angle - node.start_angle) / steps as f32;
+    Path::new(|builder| {
+        builder.move_to(point_at(center, inner, node.start_angle));
+        for i in 0..=steps {
+            builder.line_to(point_at(center, outer, node.start_angle + step * i as f32));
+        }
+        for i in (0..=steps).rev() {
+            builder.line_to(point_at(center, inner, node.start_angle + step * i as f32));
+        }
+        builder.close();
+    })
+}
+
+pub fn draw(
+    frame: &mut Frame,
+    nodes: &[SunburstNode],
+    active_node: Option<NodeID>,
+    selected_node: Option<NodeID>,
+    colors: &ThemeColors,
+    cues: bool,
+) {
+    let center = center(frame.size());
+    let ring_width = ring_width(frame.size());
+    let border = canvas::Stroke {
+        width: config::tuning().border,
+        ..Default::default()
+    }
+    .with_color(Color::from(colors.border));
+
+    for node in nodes {
+        let path = segment_path(node, center, ring_width);
+        let color = if active_node.is_some_and(|x| x == node.node_id)
+            || selected_node.is_some_and(|x| x == node.node_id)
+        {
+            Color::from(node.color).mix(Color::WHITE, 0.1)
+        } else {
+            Color::from(node.color)
+        };
+        frame.fill(&path, color);
+        let text_color = Color::from(label_color(node.color, colors.text));
+        if cues {
+            let line_dash = if node.is_file {
+                canvas::LineDash {
+                    segments: &[4.0, 3.0],
+                    offset: 0,
+                }
+            } else {
+                canvas::LineDash::default()
+            };
+            frame.stroke(
+                &path,
+                canvas::Stroke {
+                    width: 1.0,
+                    line_dash,
+                    ..Default::default()
+                }
+                .with_color(text_color),
+            );
+        } else {
+            frame.stroke(&path, border);
+        }
+
+        // label the segments that have room for some text along the middle of the ring
+        let mid_radius = if node.ring == 0 {
+            0.0
+        } else {
+            (node.ring as f32 + 0.5) * ring_width
+        };
+        let room = if node.ring == 0 {
+            ring_width * 2.0
+        } else {
+            (node.end_angle - node.start_angle) * mid_radius
+        };
+        if room > 60.0 {
+            let mid_angle = (node.start_angle + node.end_angle) / 2.0;
+            frame.fill_text(canvas::Text {
+                content: node.label.clone(),
+                position: point_at(center, mid_radius, mid_angle),
+                color: text_color,
+                max_width: room.min(ring_width * 2.0),
+                wrapping: text::Wrapping::None,
+                ellipsis: text::Ellipsis::End,
+                size: Pixels(config::tuning().text_size * 0.8),
+                align_x: text::Alignment::Center,
+                align_y: iced::alignment::Vertical::Center,
+                ..Default::default()
+            });
+        }
+    }
+
+    if cues && let Some(node) = active_node.and_then(|id| nodes.iter().find(|x| x.node_id == id)) {
+        frame.stroke(
+            &segment_path(node, center, ring_width),
+            canvas::Stroke {
+                width: 3.0,
+                ..Default::default()
+            }
+            .with_color(Color::from(colors.text)),
+        );
+    }
+
+    if let Some(node) = selected_node.and_then(|id| nodes.iter().find(|x| x.node_id == id)) {
+        frame.stroke(
+            &segment_path(node, center, ring_width),
+            canvas::Stroke {
+                width: 3.0,
+                ..Default::default()
+            }
+            .with_color(Color::from(colors.focus)),
+        );
+    }
+}
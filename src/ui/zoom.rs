@@ -0,0 +1,119 @@
+use crate::config::{MAX_ZOOM, ZOOM_DURATION};
+use crate::types::{NodeID, Rectangle};
+use iced::time::Instant;
+use iced::{Point, Size};
+
+// zoom with the mouse wheel and pan with the middle button, the treemap is laid out
+// at scale times the canvas size with its top left corner at (x, y) on the canvas
+// only kept for the shown root it was made for
+#[derive(Debug, Clone, Copy)]
+pub struct PanZoom {
+    pub root: NodeID,
+    pub scale: f32,
+    pub x: f32,
+    pub y: f32,
+}
+
+impl PanZoom {
+    pub fn new(root: NodeID) -> Self {
+        Self {
+            root,
+            scale: 1.0,
+            x: 0.0,
+            y: 0.0,
+        }
+    }
+
+    pub fn layout_rect(&self, size: Size) -> Rectangle {
+        Rectangle {
+            x: self.x,
+            y: self.y,
+            width: size.width * self.scale,
+            height: size.height * self.scale,
+        }
+    }
+
+    // the point under the cursor stays where it is
+    pub fn zoom_at(&mut self, factor: f32, anchor: Point, size: Size) {
+        let scale = (self.scale * factor).clamp(1.0, MAX_ZOOM);
+        let factor = scale / self.scale;
+        self.x = anchor.x - (anchor.x - self.x) * factor;
+        self.y = anchor.y - (anchor.y - self.y) * factor;
+        self.scale = scale;
+        self.clamp(size);
+    }
+
+    pub fn pan(&mut self, dx: f32, dy: f32, size: Size) {
+        self.x += dx;
+        self.y += dy;
+        self.clamp(size);
+    }
+
+    // the layout always covers the whole canvas
+    fn clamp(&mut self, size: Size) {
+        self.x = self.x.clamp(size.width - size.width * self.scale, 0.0);
+        self.y = self.y.clamp(size.height - size.height * self.scale, 0.0);
+    }
+}
+
+// the part of the layout shown on the canvas, starting at `from` and easing out to the
+// whole layout, all in layout coordinates
+pub struct ZoomAnimation {
+    from: Rectangle,
+    start: Instant,
+}
+
+impl ZoomAnimation {
+    // the new layout grows out of where the focused node was drawn before
+    pub fn zoom_in(old_rect: Rectangle, bounds: Rectangle) -> Self {
+        let scale_x = old_rect.width.max(1.0) / bounds.width;
+        let scale_y = old_rect.height.max(1.0) / bounds.height;
+        Self {
+            from: Rectangle {
+                x: -old_rect.x / scale_x,
+                y: -old_rect.y / scale_y,
+                width: bounds.width / scale_x,
+                height: bounds.height / scale_y,
+            },
+            start: Instant::now(),
+        }
+    }
+
+    // the node we came back from shrinks into its place in the new layout
+    pub fn zoom_out(node_rect: Rectangle) -> Self {
+        Self {
+            from: node_rect,
+            start: Instant::now(),
+        }
+    }
+
+    fn progress(&self, now: Instant) -> f32 {
+        let t = (now - self.start).as_secs_f32() / ZOOM_DURATION.as_secs_f32();
+        // ease out cubic
+        1.0 - (1.0 - t.clamp(0.0, 1.0)).powi(3)
+    }
+
+    pub fn is_done(&self, now: Instant) -> bool {
+        now - self.start >= ZOOM_DURATION
+    }
+
+    // where a rectangle of the layout is drawn at this point of the animation
+    pub fn map(&self, rect: Rectangle, bounds: Rectangle) -> Rectangle {
+        let p = self.progress(Instant::now());
+        let lerp = |a: f32, b: f32| a + (b - a) * p;
+        let viewport = Rectangle {
+            x: lerp(self.from.x, bounds.x),
+            y: lerp(self.from.y, bounds.y),
+            width: lerp(self.from.width, bounds.width),
+            height: lerp(self.from.height, bounds.height),
+        };
+        let scale_x = bounds.width / viewport.width;
+        let scale_y = bounds.height / viewport.height;
+        Rectangle {
+            x: (rect.x - viewport.x) * scale_x,
+            y: (rect.y - viewport.y) * scale_y,
+            width: rect.width * scale_x,
+            height: rect.height * scale_y,
+        }
+    }
+}
@@ -11,9 +11,53 @@ pub fn bytes_display(bytes: u64) -> String {
     }
 }
 
+// "today", "1 day ago", "3 months ago"
+pub fn age_display(secs: u64) -> String {
+    const DAY: u64 = 24 * 60 * 60;
+    let days = secs / DAY;
+    if days >= 365 {
+        format!("{:.1} years ago", days as f64 / 365.0)
+    } else if days >= 60 {
+        format!("{} months ago", days / 30)
+    } else if days > 1 {
+        format!("{} days ago", days)
+    } else if days == 1 {
+        String::from("1 day ago")
+    } else {
+        String::from("today")
+    }
+}
+
+// "rwxr-xr-x (755)"
+pub fn mode_display(mode: u32) -> String {
+    let bits: String = (0..9)
+        .map(|i| {
+            if mode & (0o400 >> i) == 0 {
+                '-'
+            } else {
+                ['r', 'w', 'x'][i % 3]
+            }
+        })
+        .collect();
+    format!("{} ({:o})", bits, mode & 0o7777)
+}
+
 /*pub fn abbreviate_string(s: &str, max_chars: usize) -> String {
     match s.char_indices().nth(max_chars) {
         None => s.to_string(),
         Some((idx, _)) => s[..idx].to_string() + "...",
     }
 }*/
+
+// 1234567 -> "1,234,567"
+pub fn count_display(count: u64) -> String {
+    let digits = count.to_string();
+    let mut out = String::new();
+    for (i, c) in digits.chars().enumerate() {
+        if i > 0 && (digits.len() - i).is_multiple_of(3) {
+            out.push(',');
+        }
+        out.push(c);
+    }
+    out
+}